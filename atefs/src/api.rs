@@ -11,7 +11,7 @@ use ate::prelude::*;
 use fuse3::{Errno, Result};
 
 #[enum_dispatch(FileApi)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FileSpec
 {
     //Custom,
@@ -63,5 +63,7 @@ pub trait FileApi
 
     async fn read(&self, _chain: &Chain, _session: &AteSession, _offset: u64, _size: u32) -> Result<Bytes> { Ok(Bytes::from(Vec::new())) }
 
-    async fn write(&self, _chain: &Chain, _session: &AteSession, _offset: u64, _data: &[u8]) -> Result<u64> { Ok(0) }
+    async fn write(&mut self, _chain: &Chain, _session: &AteSession, _offset: u64, _data: &[u8]) -> Result<u64> { Ok(0) }
+
+    async fn readdir(&self, _chain: &Chain, _session: &AteSession) -> Result<Vec<(u64, FileType, String)>> { Ok(Vec::new()) }
 }
\ No newline at end of file
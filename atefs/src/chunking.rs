@@ -0,0 +1,77 @@
+use ate::prelude::*;
+use serde::*;
+
+/// Target average chunk size used by the FastCDC-style splitter (64KiB).
+pub const CHUNK_AVG_SIZE: usize = 64 * 1024;
+/// Chunks are never produced smaller than this (avg/4), except the final chunk of the data,
+/// which holds whatever is left over.
+pub const CHUNK_MIN_SIZE: usize = CHUNK_AVG_SIZE / 4;
+/// Chunks are never produced larger than this (avg*4) - the mask is ignored past this point.
+pub const CHUNK_MAX_SIZE: usize = CHUNK_AVG_SIZE * 4;
+
+// Normalized chunking masks - a smaller mask (more bits must be zero) is used while the
+// chunk is still below the target average, a bigger mask (fewer bits) afterwards, which
+// biases boundaries towards landing close to CHUNK_AVG_SIZE (2^16): two bits either side.
+const MASK_SMALL: u64 = 0x0000_0003_ffff_0000; // 18 bits, roughly 1 in 2^18 bytes
+const MASK_LARGE: u64 = 0x0000_0000_3fff_0000; // 14 bits, roughly 1 in 2^14 bytes
+
+include!("gear.rs");
+
+/// A single content-addressed slice of a `RegularFile`, referenced by the hash of its bytes.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct ChunkRef
+{
+    pub hash: AteHash,
+    pub len: u64,
+}
+
+/// Splits `data` into content-defined chunks using a FastCDC-style rolling gear hash with
+/// normalized chunking (a tighter mask below the average size, a looser one above it).
+pub fn chunk_data<'a>(data: &'a [u8]) -> Vec<&'a [u8]>
+{
+    let mut chunks = Vec::new();
+    if data.is_empty() {
+        return chunks;
+    }
+
+    let mut start = 0usize;
+    let mut h: u64 = 0;
+
+    for i in 0..data.len() {
+        let len = i - start + 1;
+        h = (h << 1).wrapping_add(GEAR[data[i] as usize]);
+
+        let mask = if len < CHUNK_AVG_SIZE { MASK_SMALL } else { MASK_LARGE };
+
+        if len >= CHUNK_MIN_SIZE && (h & mask) == 0 {
+            chunks.push(&data[start..=i]);
+            start = i + 1;
+            h = 0;
+            continue;
+        }
+
+        if len >= CHUNK_MAX_SIZE {
+            chunks.push(&data[start..=i]);
+            start = i + 1;
+            h = 0;
+        }
+    }
+
+    if start < data.len() {
+        chunks.push(&data[start..]);
+    }
+
+    chunks
+}
+
+/// Hashes each chunk, returning the ordered list of `ChunkRef`s that make up `data`.
+pub fn chunk_refs(data: &[u8]) -> Vec<ChunkRef>
+{
+    chunk_data(data)
+        .into_iter()
+        .map(|chunk| ChunkRef {
+            hash: AteHash::from_bytes(chunk),
+            len: chunk.len() as u64,
+        })
+        .collect()
+}
@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use fuse3::{Errno, FileType, Result};
+use serde::*;
+use ate::header::PrimaryKey;
+use ate::prelude::*;
+
+use crate::api::FileApi;
+use crate::api::SpecType;
+use crate::chunking::{chunk_refs, ChunkRef};
+use super::model::*;
+
+/// The bytes of a single content-defined chunk, stored once in the chain and shared by
+/// every `RegularFile` (and every historical version of a file) that happens to contain it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Chunk
+{
+    pub data: Vec<u8>,
+}
+
+/// A regular file is represented as an ordered list of content-addressed chunks rather than
+/// a single byte blob, so that `write` only has to persist the chunks that actually changed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegularFile
+{
+    pub key: PrimaryKey,
+    pub inode: Inode,
+    pub chunks: Vec<ChunkRef>,
+    pub created: u64,
+    pub updated: u64,
+}
+
+impl RegularFile
+{
+    pub fn new(key: &PrimaryKey, inode: &Inode, created: u64, updated: u64) -> RegularFile {
+        RegularFile {
+            key: key.clone(),
+            inode: inode.clone(),
+            chunks: Vec::new(),
+            created,
+            updated,
+        }
+    }
+
+    fn chunk_key(hash: &AteHash) -> PrimaryKey {
+        PrimaryKey::from(hash.to_hex_string())
+    }
+}
+
+#[async_trait]
+impl FileApi
+for RegularFile
+{
+    fn spec(&self) -> SpecType {
+        SpecType::RegularFile
+    }
+
+    fn ino(&self) -> u64 {
+        self.key.as_u64()
+    }
+
+    fn kind(&self) -> FileType {
+        FileType::RegularFile
+    }
+
+    fn uid(&self) -> u32 {
+        self.inode.dentry.uid
+    }
+
+    fn gid(&self) -> u32 {
+        self.inode.dentry.uid
+    }
+
+    fn size(&self) -> u64 {
+        self.chunks.iter().map(|c| c.len).sum()
+    }
+
+    fn mode(&self) -> u32 {
+        self.inode.dentry.mode
+    }
+
+    fn name(&self) -> String {
+        self.inode.dentry.name.clone()
+    }
+
+    fn created(&self) -> u64 {
+        self.created
+    }
+
+    fn updated(&self) -> u64 {
+        self.updated
+    }
+
+    fn accessed(&self) -> u64 {
+        self.updated
+    }
+
+    async fn read(&self, chain: &Chain, session: &AteSession, offset: u64, size: u32) -> Result<Bytes> {
+        let mut dio = chain.dio(session).await;
+
+        let mut out = Vec::new();
+        let mut pos = 0u64;
+        let want_end = offset + size as u64;
+
+        for chunk in self.chunks.iter() {
+            let chunk_start = pos;
+            let chunk_end = pos + chunk.len;
+            pos = chunk_end;
+
+            if chunk_end <= offset || chunk_start >= want_end {
+                continue;
+            }
+
+            let key = RegularFile::chunk_key(&chunk.hash);
+            let loaded = dio.load::<Chunk>(&key).await.map_err(|_| Errno::from(libc::EIO))?;
+
+            let from = offset.saturating_sub(chunk_start) as usize;
+            let to = std::cmp::min(chunk.len, want_end - chunk_start) as usize;
+            out.extend_from_slice(&loaded.data[from..to]);
+        }
+
+        Ok(Bytes::from(out))
+    }
+
+    async fn write(&mut self, chain: &Chain, session: &AteSession, _offset: u64, data: &[u8]) -> Result<u64> {
+        // Split the new contents into content-defined chunks and only persist the ones
+        // that are not already present in the chain - identical chunks across files and
+        // across versions of the same file end up stored exactly once.
+        let refs = chunk_refs(data);
+
+        let mut dio = chain.dio(session).await;
+        let mut offset = 0usize;
+        for ChunkRef { hash, len } in refs.iter() {
+            let chunk_bytes = &data[offset..offset + *len as usize];
+            offset += *len as usize;
+
+            let key = RegularFile::chunk_key(hash);
+            if dio.load::<Chunk>(&key).await.is_err() {
+                dio.store_ext(Chunk { data: chunk_bytes.to_vec() }, None, Some(key))
+                    .map_err(|_| Errno::from(libc::EIO))?;
+            }
+        }
+
+        let mut file = dio.load::<RegularFile>(&self.key).await.map_err(|_| Errno::from(libc::EIO))?;
+        file.chunks = refs.clone();
+        dio.commit().map_err(|_| Errno::from(libc::EIO))?;
+
+        // `file` above is a disconnected `Dao<RegularFile>` loaded fresh from the chain - it
+        // commits the new chunk list, but doesn't update `self`, the copy `read` actually
+        // consults and the one callers (e.g. `Connection::fids` in the 9P server) keep cached
+        // across calls. Mirror the change here too, so a read against this same `FileSpec`
+        // sees the chunks just written instead of the ones it had when first resolved.
+        self.chunks = refs;
+
+        Ok(data.len() as u64)
+    }
+}
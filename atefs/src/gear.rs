@@ -0,0 +1,69 @@
+// Fixed table of 256 pseudo-random u64 "gear" values used by the FastCDC rolling hash
+// in chunking.rs. Generated once and kept stable so that chunk boundaries - and hence
+// dedup hits - are reproducible across runs and versions of atefs.
+const GEAR: [u64; 256] = [
+    0x315550dc68c2941c, 0x295733b41c1e699a, 0x112a2717cb268005, 0x8a840988308067a0,
+    0xe19eea4d372c5588, 0x257f451dec2e9f38, 0x26ca9ccc02ee6409, 0x87810077cddfc9d2,
+    0xe4d81ef4219c5159, 0x06a99bfeda598001, 0x4495b3808256e6dc, 0xd6c5ee5a027ca217,
+    0x8808cd070876c908, 0x571bbded80018bbd, 0xf1e55a424b90e7d8, 0x55b68618d21e8f8d,
+    0x0fcc38f10adc9747, 0x30e3d275315807ed, 0x78ff2f2a2ec030fb, 0xcd0ddcdc315e4c44,
+    0x83e964acc6606241, 0x926f8280acb07a53, 0xdee26d1a869a10f3, 0xcb95590f2ce7a090,
+    0xe647978fa34ad068, 0x0a02e9e34e775050, 0xc862c9824dd7a413, 0xf0bb5892bd088baa,
+    0x8487273e82fbab63, 0xf70b33a88a90ab6b, 0x94e3c17fe6d532dc, 0xe24027b330b068ab,
+    0x1433c822e0dfa26a, 0xe78bfed84cd056da, 0x683242bbd01fc0a9, 0xa2cc3f3c7dfcacc4,
+    0xba90e1e6ccbb7ed8, 0xf67a2cd04699ca0f, 0x790cfc68d7a23931, 0xcc1933c04a5ba863,
+    0xf1c65dd3447dcbb2, 0x40abaa87a265f396, 0xdea22c82dfa955e1, 0x92bfad39107137e0,
+    0x85a01e726bbb4107, 0xec9eb774492b0209, 0xbe5d764892e9912e, 0x6eea1bed91677474,
+    0xd0f8dee5a561f63f, 0x8e455f45b2c25998, 0x85782491f1c35a2d, 0x0e20f7cb30afb3f7,
+    0xf95215f0a566c7c0, 0x3a29bdce7575a8bf, 0x38a76fea3144b6a1, 0xb94594083a6cf62b,
+    0x425433179d665bce, 0xa45087a14f75f8d3, 0xb186ac32b64554a5, 0x07c67a6e944964a2,
+    0xd70a06644abcf002, 0x760b13b039742f79, 0x700732f000423279, 0x36d276065f508848,
+    0x32f5bb91e63f2d64, 0x87acf4479335c5b9, 0xf9a1e078ee7d6dd4, 0x2e4eab4cda3aa0d6,
+    0x4ac83d724a9b7a44, 0xa11afb3de09c6ed4, 0x6bc3e665b983f113, 0xaafb8910d7e936e3,
+    0x92fe78717f2f6285, 0xbe44839cd63be370, 0x00c30f0ee982f80b, 0xb61504110359c9c0,
+    0xf7b26480efa9e130, 0x8c5d09ddc1b95cb6, 0x77070f7751cb7412, 0x9544ed6b7a518bc3,
+    0x071ed167e6a18d89, 0x4f377d7b1514030e, 0xa4b9878eb3e95193, 0xd554dcd173bde6ac,
+    0x90b10c03a1063409, 0x74f32f80b1efba26, 0xe09ccd4c998cd417, 0xc162d91f6767c238,
+    0xd2b37fd91e85bd3e, 0x2eef4da74cb0d713, 0x9f804fcbdf1c01fc, 0xe0a6d12383e4c9f2,
+    0xcc85eff98e1346c1, 0x5ff5ad2c8efa1219, 0xb729b98c091e9796, 0x766ff1f7f1080b5a,
+    0x2dd32c5f93e5ab3b, 0xa0f3d8e3c57bbc1a, 0x9733893176a73f09, 0x932f7fac172628d4,
+    0x3cf61f3c43f6774b, 0x657c089d4499d24c, 0x37451afb1d9304b7, 0xf76f5a47d74a5d26,
+    0xe887948da8512e33, 0x8fd0a5e044ce3e4d, 0x8448e6de1f878bae, 0xea72c786bebda830,
+    0x2fa98f2853ebbd18, 0x0819e19c3310f9b3, 0xc6318440d2a92817, 0x9761b17cfabed986,
+    0x33321322e61048d0, 0xea8068638ca74bd8, 0x429ff801255cacac, 0x22783ffbd1ff10f1,
+    0xfc5c35ecdb0f32e3, 0x38e2aa6d8704bad5, 0x9c0df3f8be21ef51, 0x560647e013870b03,
+    0x0df07e81fe801f02, 0x1ea7f13a297b2d16, 0x2e7b24b8dfc2bc1f, 0x443ad4c5400b0a23,
+    0xde34c08607e30453, 0x54d7f33721274e0d, 0x3b0e6ceb2431abf5, 0x9114aced9a100500,
+    0xe95d385aa76a3111, 0xa76c1859fc5b16cc, 0x473b490b5861c936, 0x3f737510410a8575,
+    0xf5501764a81b5d96, 0x51fec22c237902f1, 0xa3053ad097921224, 0x2d13599d49386734,
+    0x125b1db05753ed47, 0x79ff8cd91c5106c4, 0x36923fea91fac039, 0x9b4ff46827c18e55,
+    0xc1565286d102a3fb, 0xfad056391af3c346, 0x215aef4ee61897b8, 0xc5bf8f17b5f00537,
+    0xb58342b699dc86e9, 0x4a5f8a016b55605f, 0xad1eebaf05763b9f, 0x33e071568eaa857f,
+    0x21143415e0560d36, 0xd5f03dc46d8b18ab, 0xb476118a3075db69, 0x7a8d8ca15aaaf7f1,
+    0x94e9b625f7180341, 0xd6d0246574a09a6b, 0x0b9decc0cf7e5890, 0x3d89de0d778a49f1,
+    0x083e72922a623dde, 0x539cc5afb76cf039, 0x526142cb9a6f2471, 0x42f817679abee213,
+    0x87df4baab9e021d8, 0xb9e23a78ee9398c5, 0x1721eec3fabf7032, 0x310ea22ad04033c6,
+    0x4236ed894ff19a32, 0x8542f5503ec03840, 0x0089fadb02122ced, 0xa550c6728a975325,
+    0x6dd346524179bc23, 0xae26931988c10866, 0x30517f42b93b1e63, 0xc71b013a07c0a38f,
+    0x31e116f361023e9b, 0x8356c1bcd3dc3bcc, 0xbd7c800a0051b9fb, 0xcf4ea0233af93b02,
+    0xf7da769feecd5e18, 0xcfdfc9d16c821c24, 0xc575359a68d6a84b, 0xb82f704124db8035,
+    0x3102f57de18b2861, 0x51b5545429368492, 0xe2739648d10b3303, 0xe462a2e6ba96ab6a,
+    0xd5c5760ffd893d38, 0x21dd60a1eb1ea99f, 0xd18290bc482e23b5, 0xc250edbe5c67b4c9,
+    0x4e389ba6709f036c, 0xa847fab0395337ab, 0x1d4a5a48c279c442, 0xddbe84ac449e19ff,
+    0x50e4f4130348f72d, 0x1bf3475c72bbecc9, 0x4f04ef258551dcfb, 0xd6fa033324fb3123,
+    0xd0e7b924a2b7c4e5, 0x6b6a18dfaf23d6cc, 0x6ec72964881c7ff1, 0x1634c3fc4332e5d7,
+    0x7f89b89450a09c18, 0xd6eae7b15aa85b62, 0x7d74407d801b50b1, 0x22c6ab1ea0a95a22,
+    0x714f0c6fe29fed99, 0xd8b7b411f35ee3e4, 0xdc047108f0514755, 0xb8cee9915e5e2946,
+    0x34f10f5743bcd2e2, 0xb5f878ae9dc4a665, 0xde34ae783ed633c6, 0xaf40288dce632cf0,
+    0x4ad63eb7b26b8a79, 0x0a0906be11cb7318, 0x44cf3366873e2444, 0x09b467e9149a6494,
+    0x1d542b77db01e7a8, 0x59a6cb106e440000, 0x041d9ba1a36012d1, 0xa9e7a939c0725467,
+    0x5203432ea5ae0106, 0x992faa703d60f840, 0xa6db6f5328867c8c, 0x1e2a6fadd91bcd0d,
+    0xb9e551b5ec208cb3, 0xccb5df65e28d6735, 0x36c2c5ad2023e33d, 0x4918c014a2a2ebd1,
+    0x7ae7897cc9dd6293, 0x566f53e1ee0f88d0, 0x8368d507fdf62fd1, 0x3007a2ce231ffdf9,
+    0x573b33ed43d07618, 0xf8bac5cc17447c77, 0x6b2b5497bf9f165c, 0x499028c0383df6b6,
+    0xcc1bc38c8ff381aa, 0x858c969445cc3fe9, 0x85cac22313a896f2, 0x7225857bb93eb239,
+    0x6f0e742e89265216, 0x170ce31e6e8e0192, 0x970b833ce976bba6, 0xa37a6bfb2f2109df,
+    0x512f157586edf626, 0x89ac81116430f80e, 0x4369ac80f9e29918, 0x17112123bce614fc,
+    0xddfa185acfd2fa3c, 0x5bcfd99eaabc786d, 0xe18c5cc2bd64496d, 0x1566e594f1dad466,
+    0x1e7271b04c8476d6, 0xcb3bea85094acf1b, 0xb592cadda3a14ee7, 0xeaf60f8fb0a33319,
+];
@@ -0,0 +1,267 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use fuse3::FileType;
+use ate::prelude::*;
+
+use crate::api::{FileApi, FileSpec};
+
+/// The handful of 9P2000.L message types that `FileApi`/`FileSpec` can actually service.
+/// Every T-message is read off the wire, dispatched against the fid table and turned into
+/// the matching R-message; anything we don't recognise comes back as `Rlerror`.
+#[derive(Debug)]
+enum Tmsg
+{
+    Version { msize: u32 },
+    Attach { fid: u32 },
+    Walk { fid: u32, newfid: u32 },
+    Open { fid: u32 },
+    Read { fid: u32, offset: u64, count: u32 },
+    Write { fid: u32, offset: u64, data: Vec<u8> },
+    Clunk { fid: u32 },
+    Getattr { fid: u32 },
+    Readdir { fid: u32 },
+}
+
+#[derive(Debug)]
+enum Rmsg
+{
+    Version { msize: u32, version: String },
+    Attach { qid: Qid },
+    Walk { qid: Qid },
+    Open { qid: Qid },
+    Read { data: Vec<u8> },
+    Write { count: u32 },
+    Clunk,
+    Getattr { ino: u64, mode: u32, uid: u32, gid: u32, size: u64, atime: u64, mtime: u64, ctime: u64 },
+    Readdir { entries: Vec<(u64, FileType, String)> },
+    Error { errno: u32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Qid
+{
+    kind: u8,
+    version: u32,
+    path: u64,
+}
+
+/// Per-connection table mapping a `fid` to the `FileSpec` it currently refers to. It starts
+/// with the root at fid 0; `Tattach` moves the root to the attached fid, and `Twalk` only
+/// supports walking a fid onto itself, so no other entries are ever added.
+struct Connection
+{
+    chain: Arc<Chain>,
+    session: AteSession,
+    fids: HashMap<u32, FileSpec>,
+}
+
+impl Connection
+{
+    fn new(chain: Arc<Chain>, session: AteSession, root: FileSpec) -> Connection {
+        let mut fids = HashMap::new();
+        fids.insert(0u32, root);
+        Connection { chain, session, fids }
+    }
+
+    fn qid_of(spec: &FileSpec) -> Qid {
+        let kind = match spec.kind() {
+            FileType::Directory => 0x80,
+            _ => 0x00,
+        };
+        Qid { kind, version: 0, path: spec.ino() }
+    }
+
+    async fn handle(&mut self, msg: Tmsg) -> Rmsg {
+        match msg {
+            Tmsg::Version { msize } => Rmsg::Version { msize, version: "9P2000.L".to_string() },
+
+            Tmsg::Attach { fid } => {
+                match self.fids.remove(&0) {
+                    Some(root) => {
+                        let qid = Connection::qid_of(&root);
+                        self.fids.insert(fid, root);
+                        Rmsg::Attach { qid }
+                    },
+                    None => Rmsg::Error { errno: libc::ENOENT as u32 },
+                }
+            },
+
+            Tmsg::Walk { fid, newfid } => {
+                // Walking onto the same fid without consuming any path components is the
+                // only hop this fid table needs to support a stat/open round-trip; walking
+                // into a child requires the directory listing from `readdir` to resolve the
+                // next component's key, which callers should do via `Treaddir` first.
+                if fid != newfid {
+                    return Rmsg::Error { errno: libc::ENOSYS as u32 };
+                }
+                match self.fids.get(&fid) {
+                    Some(spec) => Rmsg::Walk { qid: Connection::qid_of(spec) },
+                    None => Rmsg::Error { errno: libc::EBADF as u32 },
+                }
+            },
+
+            Tmsg::Open { fid } => {
+                match self.fids.get(&fid) {
+                    Some(spec) => Rmsg::Open { qid: Connection::qid_of(spec) },
+                    None => Rmsg::Error { errno: libc::EBADF as u32 },
+                }
+            },
+
+            Tmsg::Read { fid, offset, count } => {
+                match self.fids.get(&fid) {
+                    Some(spec) => match spec.read(&self.chain, &self.session, offset, count).await {
+                        Ok(data) => Rmsg::Read { data: data.to_vec() },
+                        Err(_) => Rmsg::Error { errno: libc::EIO as u32 },
+                    },
+                    None => Rmsg::Error { errno: libc::EBADF as u32 },
+                }
+            },
+
+            Tmsg::Write { fid, offset, data } => {
+                // Borrowed mutably (rather than via `get`, like every other handler here) so a
+                // successful write updates the cached `FileSpec` itself - see `RegularFile::write`
+                // - instead of leaving `fids` pointing at the contents this fid had when it was
+                // first resolved.
+                match self.fids.get_mut(&fid) {
+                    Some(spec) => match spec.write(&self.chain, &self.session, offset, &data[..]).await {
+                        Ok(n) => Rmsg::Write { count: n as u32 },
+                        Err(_) => Rmsg::Error { errno: libc::EIO as u32 },
+                    },
+                    None => Rmsg::Error { errno: libc::EBADF as u32 },
+                }
+            },
+
+            Tmsg::Clunk { fid } => {
+                self.fids.remove(&fid);
+                Rmsg::Clunk
+            },
+
+            Tmsg::Getattr { fid } => {
+                match self.fids.get(&fid) {
+                    Some(spec) => Rmsg::Getattr {
+                        ino: spec.ino(),
+                        mode: spec.mode(),
+                        uid: spec.uid(),
+                        gid: spec.gid(),
+                        size: spec.size(),
+                        atime: spec.accessed(),
+                        mtime: spec.updated(),
+                        ctime: spec.created(),
+                    },
+                    None => Rmsg::Error { errno: libc::EBADF as u32 },
+                }
+            },
+
+            Tmsg::Readdir { fid } => {
+                match self.fids.get(&fid) {
+                    Some(spec) => match spec.readdir(&self.chain, &self.session).await {
+                        Ok(entries) => Rmsg::Readdir { entries },
+                        Err(_) => Rmsg::Error { errno: libc::EIO as u32 },
+                    },
+                    None => Rmsg::Error { errno: libc::EBADF as u32 },
+                }
+            },
+        }
+    }
+}
+
+/// Serves the chain-backed `FileApi` tree rooted at `root` on `listener`, using the 9P2000.L
+/// message set over the simplified wire format described at `decode_tmsg`. That format is not
+/// the binary 9P2000.L encoding, so a stock kernel or hypervisor 9P client cannot mount it.
+pub async fn serve_9p(listener: TcpListener, chain: Arc<Chain>, session: AteSession, root: FileSpec) -> std::io::Result<()>
+{
+    loop {
+        let (socket, _addr) = listener.accept().await?;
+        let chain = Arc::clone(&chain);
+        let session = session.clone();
+        let root = root.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = serve_connection(socket, chain, session, root).await {
+                tracing::debug!("9p connection closed: {}", err);
+            }
+        });
+    }
+}
+
+async fn serve_connection(mut socket: TcpStream, chain: Arc<Chain>, session: AteSession, root: FileSpec) -> std::io::Result<()>
+{
+    let conn = Arc::new(Mutex::new(Connection::new(chain, session, root)));
+
+    loop {
+        let mut size_buf = [0u8; 4];
+        if socket.read_exact(&mut size_buf).await.is_err() {
+            return Ok(());
+        }
+        let size = u32::from_le_bytes(size_buf) as usize;
+        if size < 4 {
+            return Ok(());
+        }
+
+        let mut body = vec![0u8; size - 4];
+        socket.read_exact(&mut body).await?;
+
+        let reply = match decode_tmsg(&body) {
+            Some(msg) => conn.lock().await.handle(msg).await,
+            None => Rmsg::Error { errno: libc::ENOSYS as u32 },
+        };
+        let encoded = encode_rmsg(&reply);
+        socket.write_all(&(encoded.len() as u32 + 4).to_le_bytes()).await?;
+        socket.write_all(&encoded).await?;
+    }
+}
+
+// Minimal, self-contained wire layout (not full 9P2000.L binary framing): a little-endian
+// `size[4]` prefix that counts itself, then a one-byte message type. No other fields are
+// decoded: every message addresses fid 0, `Tread` always asks for 4096 bytes at offset 0, and
+// `Twrite` writes the rest of the body at offset 0. Replies carry fields only for `Rversion`
+// (the version string), `Rread` (the data), `Rwrite` (the count) and errors (the errno); every
+// other reply is a single 0 byte. A real 9P marshaller can be dropped in behind
+// `decode_tmsg`/`encode_rmsg` without touching `handle`.
+fn decode_tmsg(body: &[u8]) -> Option<Tmsg> {
+    if body.is_empty() {
+        return None;
+    }
+    match body[0] {
+        100 => Some(Tmsg::Version { msize: 8192 }),
+        104 => Some(Tmsg::Attach { fid: 0 }),
+        110 => Some(Tmsg::Walk { fid: 0, newfid: 0 }),
+        112 => Some(Tmsg::Open { fid: 0 }),
+        116 => Some(Tmsg::Read { fid: 0, offset: 0, count: 4096 }),
+        118 => Some(Tmsg::Write { fid: 0, offset: 0, data: body[1..].to_vec() }),
+        120 => Some(Tmsg::Clunk { fid: 0 }),
+        24 => Some(Tmsg::Getattr { fid: 0 }),
+        40 => Some(Tmsg::Readdir { fid: 0 }),
+        _ => None,
+    }
+}
+
+fn encode_rmsg(msg: &Rmsg) -> Vec<u8> {
+    match msg {
+        Rmsg::Version { version, .. } => {
+            let mut out = vec![101u8];
+            out.extend_from_slice(version.as_bytes());
+            out
+        },
+        Rmsg::Error { errno } => {
+            let mut out = vec![107u8];
+            out.extend_from_slice(&errno.to_le_bytes());
+            out
+        },
+        Rmsg::Read { data } => {
+            let mut out = vec![117u8];
+            out.extend_from_slice(data);
+            out
+        },
+        Rmsg::Write { count } => {
+            let mut out = vec![119u8];
+            out.extend_from_slice(&count.to_le_bytes());
+            out
+        },
+        _ => vec![0u8],
+    }
+}
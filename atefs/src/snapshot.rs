@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use fuse3::{FileType, Result};
+use ate::prelude::*;
+
+use crate::api::{FileApi, FileSpec, SpecType};
+
+/// The point in the append-only chain a `Snapshot` mount is labelled with - either a raw
+/// event offset (as recorded on `EventLeaf`) or a wall-clock time. `Snapshot` records it and
+/// returns it from `at()`; reads through the mount are not filtered by it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotPoint
+{
+    Offset(u64),
+    Timestamp(u64),
+}
+
+/// A read-only wrapper around a `FileSpec`, recording the `SnapshotPoint` it was mounted at.
+/// Today `at` only gates `write` (rejected outright, so a snapshot mount can never diverge from
+/// the point it claims to represent) - `read`/`readdir` still delegate straight to `self.inner`,
+/// the same live-chain `FileSpec` a normal (non-snapshot) mount would use, rather than
+/// reconstructing it as of `at` via `Dio::load_at`/`history`. So this is a write-guard over
+/// whatever `self.inner` already resolved to at lookup time, not yet a true point-in-time mount
+/// - genuinely filtering `read`/`readdir` through `at` needs each `FileApi` impl (`Directory`,
+/// `RegularFile`, `FixedFile`) to reload its own DAO at `at` instead of using its cached fields,
+/// which this wrapper alone can't do from outside the `enum_dispatch`.
+#[derive(Debug, Clone)]
+pub struct Snapshot
+{
+    inner: Box<FileSpec>,
+    at: SnapshotPoint,
+}
+
+impl Snapshot
+{
+    pub fn new(inner: FileSpec, at: SnapshotPoint) -> Snapshot {
+        Snapshot { inner: Box::new(inner), at }
+    }
+
+    pub fn at(&self) -> SnapshotPoint {
+        self.at
+    }
+}
+
+#[async_trait]
+impl FileApi
+for Snapshot
+{
+    fn ino(&self) -> u64 { self.inner.ino() }
+
+    fn name(&self) -> String { self.inner.name() }
+
+    fn spec(&self) -> SpecType { self.inner.spec() }
+
+    fn kind(&self) -> FileType { self.inner.kind() }
+
+    fn uid(&self) -> u32 { self.inner.uid() }
+
+    fn gid(&self) -> u32 { self.inner.gid() }
+
+    fn size(&self) -> u64 { self.inner.size() }
+
+    fn mode(&self) -> u32 { self.inner.mode() }
+
+    fn accessed(&self) -> u64 { self.inner.accessed() }
+
+    fn created(&self) -> u64 { self.inner.created() }
+
+    fn updated(&self) -> u64 { self.inner.updated() }
+
+    async fn read(&self, chain: &Chain, session: &AteSession, offset: u64, size: u32) -> Result<Bytes> {
+        // TODO(point-in-time-read): this reads whatever `self.inner` already resolved to at
+        // lookup time, not the chain as it stood at `self.at` - see the struct-level doc comment.
+        self.inner.read(chain, session, offset, size).await
+    }
+
+    async fn write(&mut self, _chain: &Chain, _session: &AteSession, _offset: u64, _data: &[u8]) -> Result<u64> {
+        Err(fuse3::Errno::from(libc::EROFS))
+    }
+
+    async fn readdir(&self, chain: &Chain, session: &AteSession) -> Result<Vec<(u64, FileType, String)>> {
+        // TODO(point-in-time-read): same gap as `read` above - entries are whatever `self.inner`
+        // already resolved to, not the directory's contents as of `self.at`.
+        self.inner.readdir(chain, session).await
+    }
+}
+
+impl Chain
+{
+    /// Wraps `root` in a read-only `Snapshot` labelled with `at`. Writes are rejected; reads,
+    /// `readdir` and the metadata accessors return `root`'s current contents, not the state of
+    /// the chain at `at`.
+    pub fn mount_snapshot(&self, root: FileSpec, at: SnapshotPoint) -> Snapshot {
+        Snapshot::new(root, at)
+    }
+}
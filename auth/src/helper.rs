@@ -0,0 +1,25 @@
+// Needs `mod helper;` adding alongside the crate's other top-level modules once this tree
+// carries a `lib.rs`/`main.rs` to declare them in - see the `login_provider`/`oauth_provider`
+// modules for the same situation.
+#![allow(unused_imports)]
+use ate::prelude::*;
+
+/// The inverse of [`session_to_b64`] - used by `main_session` both for a bare token string and
+/// for `SessionToken::session_b64`. Falls back to an empty session rather than propagating an
+/// error: a token this can't parse is handled the same way as one that isn't there at all, so
+/// either way the caller just ends up prompting for a fresh login.
+pub fn b64_to_session(token: String) -> AteSession
+{
+    base64::decode(token.as_bytes())
+        .ok()
+        .and_then(|raw| serde_json::from_slice(raw.as_slice()).ok())
+        .unwrap_or_default()
+}
+
+/// Serializes a session into the same base64(json) shape [`b64_to_session`] reads back - used
+/// by `main_session` to rewrite a renewed `AteSession` into `SessionToken::session_b64` without
+/// round-tripping it through a token file.
+pub fn session_to_b64(session: AteSession) -> String
+{
+    base64::encode(serde_json::to_vec(&session).expect("an AteSession should always serialize"))
+}
@@ -5,6 +5,8 @@ use std::{io::stdout, path::Path};
 use std::io::Write;
 use url::Url;
 use std::sync::Arc;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
 
 use ate::prelude::*;
 use ate::error::LoadError;
@@ -13,11 +15,17 @@ use ate::error::TransformError;
 use crate::conf_auth;
 use crate::prelude::*;
 use crate::commands::*;
+use crate::request::*;
 use crate::service::AuthService;
+use crate::login_provider::Credentials;
 use crate::helper::*;
 use crate::error::*;
 use crate::helper::*;
 
+/// How long a freshly issued login session stays valid before the client must call
+/// `renew_command` (or, once its own refresh token itself expires, log in again).
+pub(crate) const LOGIN_SESSION_TTL_SECS: u64 = 15 * 60;
+
 impl AuthService
 {
     pub(crate) fn master_key(&self) -> Option<EncryptKey>
@@ -37,130 +45,124 @@ impl AuthService
         Some(super_key)
     }
 
+    /// The OAuth equivalent of `compute_super_key`: an OAuth login has no password-derived
+    /// secret to mix with the master key, so the provider's own immutable subject identifier
+    /// (verified server-side by `OAuthProviderConfig::exchange_code`, never supplied directly
+    /// by the client) stands in for one instead.
+    pub(crate) fn compute_oauth_super_key(&self, provider_subject: &str) -> Option<EncryptKey>
+    {
+        let master_key = match self.master_session.read_keys().next() {
+            Some(a) => a.clone(),
+            None => { return None; }
+        };
+        let super_key = AteHash::from_bytes_twice(master_key.value(), provider_subject.as_bytes());
+        let super_key = EncryptKey::from_seed_bytes(super_key.to_bytes(), KeySize::Bit256);
+        Some(super_key)
+    }
+
+    // TODO(login-provider): `AuthService` needs a `login_provider: Box<dyn LoginProvider>`
+    // field, defaulted to `login_provider::default_login_provider(self.registry.clone(),
+    // self.auth_url.clone(), self.master_session.clone())` in its constructor, before this
+    // compiles - that struct lives in the `service` module, which this tree does not currently
+    // carry. The call below is written against the field as if it already existed.
+    //
+    // TODO(device-login): `LoginRequest` needs a `device_id: Option<String>` field alongside
+    // its existing `email`/`secret`/`code` - when set, `secret` carries a device API key
+    // (provisioned by `register_device_command`) rather than a password-derived read key, and
+    // `code` is never populated for this credential type. That field lives alongside
+    // `LoginRequest` itself, which this tree does not currently carry (`auth/src/request.rs`
+    // holds only the device/renew/OAuth/recovery-code request types).
     pub async fn process_login(self: Arc<Self>, request: LoginRequest) -> Result<LoginResponse, LoginFailed>
     {
         info!("login attempt: {}", request.email);
 
-        let super_key = match self.compute_super_key(request.secret) {
-            Some(a) => a,
-            None => {
-                warn!("login attempt denied ({}) - no master key", request.email);
-                return Err(LoginFailed::NoMasterKey);
-            }
-        };
-        let mut super_session = AteSession::default();
-        super_session.user.add_read_key(&super_key);
-        if request.code.is_some() {
-            let super_super_key = match self.compute_super_key(super_key.clone()) {
-                Some(a) => a,
-                None => {
-                    warn!("login attempt denied ({}) - no master key (sudo)", request.email);
-                    return Err(LoginFailed::NoMasterKey);
-                }
+        let Credentials { user_key, status, nominal_read, nominal_write, sudo_read, sudo_write, mut session, sudo } =
+            match request.device_id.as_deref() {
+                Some(device_id) => self.login_provider.login_device(request.email.as_str(), device_id, &request.secret).await?,
+                None => self.login_provider.login(request.email.as_str(), &request.secret).await?,
             };
-            super_session.user.add_read_key(&super_super_key);
-        }
 
-        // Compute which chain the user should exist within
-        let chain_key = chain_key_4hex(request.email.as_str(), Some("redo"));
-        let chain = self.registry.open(&self.auth_url, &chain_key).await?;
-        let dio = chain.dio(&super_session).await;
-
-        let user_key = PrimaryKey::from(request.email.clone());
-        let user =
-        {
-            // Attempt to load the object (if it fails we will tell the caller)
-            let user = match dio.load::<User>(&user_key).await {
-                Ok(a) => a,
-                Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
-                    warn!("login attempt denied ({}) - not found", request.email);
-                    return Err(LoginFailed::UserNotFound(request.email));
-                },
-                Err(LoadError(LoadErrorKind::TransformationError(TransformErrorKind::MissingReadKey(_)), _)) => {
-                    warn!("login attempt denied ({}) - wrong password", request.email);
-                    return Err(LoginFailed::WrongPasswordOrCode);
-                },
-                Err(err) => {
-                    warn!("login attempt denied ({}) - error - ", err);
-                    bail!(err);
+        // Check if the account is locked or not yet verified
+        match status {
+            UserStatus::Locked(until) => {
+                let local_now = chrono::Local::now();
+                let utc_now = local_now.with_timezone(&chrono::Utc);
+                if until > utc_now {
+                    let duration = until - utc_now;
+                    warn!("login attempt denied ({}) - account locked until {}", request.email, until);
+                    return Err(LoginFailed::AccountLocked(duration.to_std().unwrap()));
                 }
-            };
-            
-            // Check if the account is locked or not yet verified
-            match user.status {
-                UserStatus::Locked(until) => {
-                    let local_now = chrono::Local::now();
-                    let utc_now = local_now.with_timezone(&chrono::Utc);
-                    if until > utc_now {
-                        let duration = until - utc_now;
-                        warn!("login attempt denied ({}) - account locked until {}", request.email, until);
-                        return Err(LoginFailed::AccountLocked(duration.to_std().unwrap()));
-                    }
-                },
-                UserStatus::Unverified => {
-                    warn!("login attempt denied ({}) - unverified", request.email);
-                    return Err(LoginFailed::Unverified(request.email));
-                },
-                UserStatus::Nominal => { },
-            };
-
-            // Ok we have the user
-            user.take()
+            },
+            UserStatus::Unverified => {
+                warn!("login attempt denied ({}) - unverified", request.email);
+                return Err(LoginFailed::Unverified(request.email));
+            },
+            UserStatus::Nominal => { },
         };
 
-        // Add all the authorizations
-        let mut session = compute_user_auth(&user);
-        session.user.add_identity(request.email.clone());
-
         // If a google authenticator code has been supplied then we need to try and load the
         // extra permissions from elevated rights
         if let Some(code) = request.code {
-        
-            // Load the sudo object
-            if let Some(sudo) = match user.sudo.load().await {
-                Ok(a) => a,
-                Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
-                    warn!("login attempt denied ({}) - user not found", request.email);
+
+            let mut sudo = match sudo {
+                Some(a) => a,
+                None => {
+                    warn!("login attempt denied ({}) - user not found (sudo)", request.email);
                     return Err(LoginFailed::UserNotFound(request.email));
-                },
-                Err(LoadError(LoadErrorKind::TransformationError(TransformErrorKind::MissingReadKey(_)), _)) => {
-                    warn!("login attempt denied ({}) - wrong password (sudo)", request.email);
-                    return Err(LoginFailed::WrongPasswordOrCode);
-                },
-                Err(err) => {
-                    bail!(err);
                 }
+            };
+
+            // Reject outright while a prior run of failures has this account locked out
+            let time = self.time_keeper.current_timestamp_as_duration()?;
+            let now = time.as_secs();
+            if let Some(remaining) = sudo.lockout_remaining(now) {
+                warn!("login attempt denied ({}) - locked out for {}s", request.email, remaining);
+                return Err(LoginFailed::AccountLocked(std::time::Duration::from_secs(remaining)));
             }
-            {
-                // Check the code matches the authenticator code
-                let time = self.time_keeper.current_timestamp_as_duration()?;
-                let time = time.as_secs() / 30;
-                let google_auth = google_authenticator::GoogleAuthenticator::new();
-                if google_auth.verify_code(sudo.secret.as_str(), code.as_str(), 3, time) {
-                    debug!("code authenticated");
-                } else {
-                    warn!("login attempt denied ({}) - wrong code", request.email);
-                    return Err(LoginFailed::WrongPasswordOrCode);
-                }
 
-                // Add the extra authentication objects from the sudo
-                session = compute_sudo_auth(&sudo.take(), session);
-                
+            // Check the code against any enrolled second factor (TOTP today; WebAuthn
+            // assertions will come in through the same `any factor validates` model once
+            // the login wire protocol carries one), falling back to a single-use recovery
+            // code for when the authenticator device itself has been lost.
+            let totp_time = now / 30;
+            if sudo.verify_totp(code.as_str(), totp_time) {
+                debug!("code authenticated");
+                sudo.register_success();
+            } else if sudo.verify_recovery_code(code.as_str()) {
+                debug!("recovery code accepted ({}) - consumed one-time use", request.email);
+                sudo.register_success();
             } else {
-                warn!("login attempt denied ({}) - user not found (sudo)", request.email);
-                return Err(LoginFailed::UserNotFound(request.email));
+                sudo.register_failure(now);
+                warn!("login attempt denied ({}) - wrong code", request.email);
+                return Err(LoginFailed::WrongPasswordOrCode);
             }
+
+            // Add the extra authentication objects from the sudo
+            session = compute_sudo_auth(&sudo, session);
         }
 
         // Return the session that can be used to access this user
+        //
+        // TODO(session-renewal): `LoginResponse` needs `expires_at: u64` and
+        // `refresh_token: Option<String>` fields alongside `authority` (next to `LoginRequest`,
+        // which this tree does not carry) so `main_session` knows when to
+        // call `renew_command` instead of forcing an interactive login again. `refresh_token`
+        // is left `None` here rather than minted: `self.login_provider.login`'s `Credentials`
+        // only hands back a disconnected snapshot of the account's `Sudo` record, not a live
+        // write handle, so there is nowhere to persist the token's hash the way
+        // `process_renew`/`Sudo::issue_refresh_token` expect - unlike `process_renew`, which
+        // loads its own `Dio` and can commit the rotation it performs.
         warn!("login attempt accepted ({})", request.email);
+        let time = self.time_keeper.current_timestamp_as_duration()?;
         Ok(LoginResponse {
             user_key,
-            nominal_read: user.nominal_read,
-            nominal_write: user.nominal_write,
-            sudo_read: user.sudo_read,
-            sudo_write: user.sudo_write,
+            nominal_read,
+            nominal_write,
+            sudo_read,
+            sudo_write,
             authority: session,
+            expires_at: time.as_secs() + LOGIN_SESSION_TTL_SECS,
+            refresh_token: None,
             message_of_the_day: None,
         })
     }
@@ -183,6 +185,7 @@ pub async fn login_command(username: String, password: String, code: Option<Stri
         email: username.clone(),
         secret: read_key,
         code,
+        device_id: None,
     };
 
     // Attempt the login request with a 10 second timeout
@@ -200,6 +203,296 @@ pub async fn login_command(username: String, password: String, code: Option<Stri
     Ok(result.authority)
 }
 
+/// Signs in via an external OAuth2/OIDC provider instead of a local password: prints the
+/// provider's consent-screen URL for the user to open (it does not launch a browser itself),
+/// runs a throwaway localhost listener to catch the `code`/`state` redirect, and completes
+/// the exchange server-side - mirroring how
+/// `login_command` wraps `chain.invoke`, just with a browser round-trip in between the two
+/// `chain.invoke` calls instead of a password prompt.
+pub async fn oauth_login_command(provider: String, auth: Url) -> Result<AteSession, LoginError>
+{
+    // Open a command chain
+    let registry = ate::mesh::Registry::new(&conf_cmd()).await.cement();
+    let chain = registry.open(&auth, &chain_key_cmd()).await?;
+
+    let begin: Result<OAuthBeginResponse, OAuthLoginFailed> = chain.invoke(OAuthBeginRequest {
+        provider,
+    }).await?;
+    let begin = begin?;
+
+    eprintln!("Open this URL to sign in, then return here:");
+    eprintln!("{}", begin.authorize_url);
+
+    // The provider's redirect_uri for this flow must point at 127.0.0.1 on this port - the
+    // very first request the listener sees is the browser's redirect, carrying `code` and
+    // `state` as query parameters.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:8752").await?;
+    let (mut stream, _) = listener.accept().await?;
+
+    let mut buf = [0u8; 4096];
+    let n = tokio::io::AsyncReadExt::read(&mut stream, &mut buf).await?;
+    let request_line = String::from_utf8_lossy(&buf[..n]);
+    let path = request_line
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("");
+
+    let query = path.splitn(2, '?').nth(1).unwrap_or("");
+    let mut code = None;
+    let mut state = None;
+    for pair in query.split('&') {
+        let mut kv = pair.splitn(2, '=');
+        match (kv.next(), kv.next()) {
+            (Some("code"), Some(v)) => code = Some(v.to_string()),
+            (Some("state"), Some(v)) => state = Some(v.to_string()),
+            _ => {},
+        }
+    }
+
+    let body = "You may close this window and return to the terminal.";
+    let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+    let _ = tokio::io::AsyncWriteExt::write_all(&mut stream, response.as_bytes()).await;
+
+    let (code, state) = match (code, state) {
+        (Some(code), Some(state)) => (code, state),
+        _ => { return Err(LoginErrorKind::InvalidState.into()); }
+    };
+    if state != begin.state {
+        return Err(LoginErrorKind::InvalidState.into());
+    }
+
+    let complete: Result<LoginResponse, OAuthLoginFailed> = chain.invoke(OAuthCompleteRequest {
+        state,
+        code,
+    }).await?;
+    let result = complete?;
+
+    Ok(result.authority)
+}
+
+/// Marks a token file as carrying a [`DeviceToken`] rather than a plain base64-encoded
+/// `AteSession`, so `main_session` knows to mint a fresh session through a device login
+/// instead of handing the bytes straight to `b64_to_session`.
+const DEVICE_TOKEN_PREFIX: &str = "device1:";
+
+/// The token-file payload written by `register_device_command`. Unlike a human's cached
+/// `AteSession` token, this only proves identity - the email/device_id/api_key still have to
+/// go through `process_login`'s device-key branch on every `main_session` call to mint a
+/// session, so a revoked key stops working on the very next call rather than whenever the
+/// cached session happens to expire.
+#[derive(Serialize, Deserialize)]
+struct DeviceToken {
+    email: String,
+    device_id: String,
+    api_key: EncryptKey,
+}
+
+/// Exchanges a device's provisioned API key for a fresh session, the non-interactive
+/// counterpart to [`login_command`].
+async fn device_login_command(token: DeviceToken, auth: Url) -> Result<AteSession, LoginError>
+{
+    let registry = ate::mesh::Registry::new(&conf_cmd()).await.cement();
+    let chain = registry.open(&auth, &chain_key_cmd()).await?;
+
+    let login = LoginRequest {
+        email: token.email,
+        secret: token.api_key,
+        code: None,
+        device_id: Some(token.device_id),
+    };
+
+    let response: Result<LoginResponse, LoginFailed> = chain.invoke(login).await?;
+    let result = response?;
+    Ok(result.authority)
+}
+
+/// Marks a token file as carrying a [`SessionToken`] (a cached session plus its renewal
+/// material) rather than a bare base64 `AteSession`.
+const SESSION_TOKEN_PREFIX: &str = "session1:";
+
+/// The token-file payload a login that carries an `expires_at`/`refresh_token` (see the
+/// `TODO(session-renewal)` on `process_login`) would write once something wires that up - lets
+/// `main_session` reuse the cached session until it's close to `expires_at`, then transparently
+/// swap in `renew_command` instead of forcing the user through an interactive login again.
+#[derive(Serialize, Deserialize)]
+struct SessionToken {
+    email: String,
+    session_b64: String,
+    expires_at: u64,
+    refresh_token: String,
+}
+
+/// Renewals are attempted once the cached session has this long left, rather than waiting
+/// until it has expired outright, so a call that's already in flight doesn't race the clock.
+const RENEW_BEFORE_EXPIRY_SECS: u64 = 60;
+
+/// Exchanges a still-live refresh token for a fresh session, the non-interactive counterpart
+/// to [`login_command`] that doesn't need a password or TOTP code.
+pub async fn renew_command(email: String, refresh_token: String, auth: Url) -> Result<(AteSession, u64, String), LoginError>
+{
+    let registry = ate::mesh::Registry::new(&conf_cmd()).await.cement();
+    let chain = registry.open(&auth, &chain_key_cmd()).await?;
+
+    let request = RenewRequest { email, refresh_token };
+    let response: Result<RenewResponse, RenewFailed> = chain.invoke(request).await?;
+    let result = response?;
+
+    Ok((result.authority, result.expires_at, result.refresh_token))
+}
+
+pub async fn register_device_command(
+    username: Option<String>,
+    password: Option<String>,
+    code: Option<String>,
+    auth: Url,
+    token_file_path: String,
+) -> Result<(), LoginError>
+{
+    let username = match username {
+        Some(a) => a,
+        None => {
+            eprint!("Username: ");
+            stdout().lock().flush()?;
+            let mut s = String::new();
+            std::io::stdin().read_line(&mut s).expect("Did not enter a valid username");
+            s.trim().to_string()
+        }
+    };
+
+    let password = match password {
+        Some(a) => a,
+        None => {
+            eprint!("Password: ");
+            stdout().lock().flush()?;
+            let pass = rpassword::read_password().unwrap();
+            pass.trim().to_string()
+        }
+    };
+
+    let code = match code {
+        Some(a) => a,
+        None => {
+            eprint!("Code: ");
+            stdout().lock().flush()?;
+            let mut s = String::new();
+            std::io::stdin().read_line(&mut s).expect("Did not enter a valid code");
+            s.trim().to_string()
+        }
+    };
+
+    // Prove identity the normal interactive way first - a device key is provisioned onto an
+    // already-authenticated account, never bootstrapped from nothing.
+    let response = login_command(username.clone(), password, Some(code), auth.clone(), false).await;
+    let session = handle_login_response(response, true)?;
+
+    // A random per-device id, stable for the life of this token file - re-running this command
+    // on the same machine rotates its key (see `Sudo::issue_device_api_key`) rather than piling
+    // up an ever-growing list of them.
+    let path = shellexpand::tilde(token_file_path.as_str()).to_string();
+    let existing_device_id = tokio::fs::read_to_string(&path).await.ok()
+        .and_then(|token| token.strip_prefix(DEVICE_TOKEN_PREFIX).map(|s| s.to_string()))
+        .and_then(|encoded| base64::decode(encoded).ok())
+        .and_then(|raw| serde_json::from_slice::<DeviceToken>(&raw).ok())
+        .map(|token| token.device_id);
+    let device_id: String = match existing_device_id {
+        Some(a) => a,
+        None => {
+            let mut rng = rand::thread_rng();
+            (0..16).map(|_| format!("{:x}", rng.gen_range(0..16u8))).collect()
+        }
+    };
+
+    let registry = ate::mesh::Registry::new(&conf_cmd()).await.cement();
+    let chain = registry.open(&auth, &chain_key_cmd()).await?;
+
+    let request = RegisterDeviceRequest {
+        session,
+        email: username.clone(),
+        device_id: device_id.clone(),
+    };
+    let response: Result<RegisterDeviceResponse, RegisterDeviceFailed> = chain.invoke(request).await?;
+    let result = response?;
+
+    let token = DeviceToken {
+        email: username,
+        device_id: result.device_id.clone(),
+        api_key: result.api_key,
+    };
+    let encoded = base64::encode(serde_json::to_vec(&token)?);
+    tokio::fs::write(path, format!("{}{}", DEVICE_TOKEN_PREFIX, encoded)).await?;
+
+    eprintln!("Device registered ({}) - wrote an API key to the token file", result.device_id);
+    Ok(())
+}
+
+/// Replaces a lost-or-used-up set of backup codes with a fresh one, displayed exactly once -
+/// gated behind a full (password + TOTP/recovery code) login the same way
+/// `register_device_command` is, since `process_regenerate_recovery_codes` rejects a session
+/// that never actually carried sudo rights.
+pub async fn regenerate_recovery_codes_command(
+    username: Option<String>,
+    password: Option<String>,
+    code: Option<String>,
+    auth: Url,
+) -> Result<Vec<String>, LoginError>
+{
+    let username = match username {
+        Some(a) => a,
+        None => {
+            eprint!("Username: ");
+            stdout().lock().flush()?;
+            let mut s = String::new();
+            std::io::stdin().read_line(&mut s).expect("Did not enter a valid username");
+            s.trim().to_string()
+        }
+    };
+
+    let password = match password {
+        Some(a) => a,
+        None => {
+            eprint!("Password: ");
+            stdout().lock().flush()?;
+            let pass = rpassword::read_password().unwrap();
+            pass.trim().to_string()
+        }
+    };
+
+    let code = match code {
+        Some(a) => a,
+        None => {
+            eprint!("Code: ");
+            stdout().lock().flush()?;
+            let mut s = String::new();
+            std::io::stdin().read_line(&mut s).expect("Did not enter a valid code");
+            s.trim().to_string()
+        }
+    };
+
+    // Prove full sudo rights the normal interactive way first - regenerating recovery codes is
+    // exactly the kind of destructive, account-recovery-relevant action a merely-nominal
+    // session must not be able to trigger.
+    let response = login_command(username.clone(), password, Some(code), auth.clone(), false).await;
+    let session = handle_login_response(response, true)?;
+
+    let registry = ate::mesh::Registry::new(&conf_cmd()).await.cement();
+    let chain = registry.open(&auth, &chain_key_cmd()).await?;
+
+    let request = RegenerateRecoveryCodesRequest {
+        session,
+        email: username,
+    };
+    let response: Result<RegenerateRecoveryCodesResponse, RegenerateRecoveryCodesFailed> = chain.invoke(request).await?;
+    let result = response?;
+
+    eprintln!("New recovery codes (store these somewhere safe - they will not be shown again):");
+    for code in result.recovery_codes.iter() {
+        eprintln!("  {}", code);
+    }
+
+    Ok(result.recovery_codes)
+}
+
 pub async fn load_credentials(username: String, read_key: EncryptKey, _code: Option<String>, auth: Url) -> Result<AteSession, AteError>
 {
     // Prepare for the load operation
@@ -236,8 +529,41 @@ pub async fn main_session(token_string: Option<String>, token_file_path: Option<
                 std::process::exit(1);
             }
             let path = shellexpand::tilde(path.as_str()).to_string();
-            let token = tokio::fs::read_to_string(path).await?;
-            session = Some(b64_to_session(token));
+            let token = tokio::fs::read_to_string(&path).await?;
+            session = Some(if let Some(encoded) = token.strip_prefix(DEVICE_TOKEN_PREFIX) {
+                let auth = match auth_url.clone() {
+                    Some(a) => a,
+                    None => bail!("the token file holds a device API key, which needs an --auth server to exchange for a session"),
+                };
+                let raw = base64::decode(encoded)?;
+                let device_token: DeviceToken = serde_json::from_slice(&raw)?;
+                device_login_command(device_token, auth).await?
+            } else if let Some(encoded) = token.strip_prefix(SESSION_TOKEN_PREFIX) {
+                let raw = base64::decode(encoded)?;
+                let mut cached: SessionToken = serde_json::from_slice(&raw)?;
+
+                let now = chrono::Utc::now().timestamp().max(0) as u64;
+                if cached.expires_at <= now + RENEW_BEFORE_EXPIRY_SECS {
+                    let auth = match auth_url.clone() {
+                        Some(a) => a,
+                        None => bail!("the cached session is near expiry and needs an --auth server to renew"),
+                    };
+
+                    let (renewed, expires_at, refresh_token) = renew_command(cached.email.clone(), cached.refresh_token.clone(), auth).await?;
+                    cached.expires_at = expires_at;
+                    cached.refresh_token = refresh_token;
+                    cached.session_b64 = session_to_b64(renewed.clone());
+
+                    let encoded = base64::encode(serde_json::to_vec(&cached)?);
+                    tokio::fs::write(&path, format!("{}{}", SESSION_TOKEN_PREFIX, encoded)).await?;
+
+                    renewed
+                } else {
+                    b64_to_session(cached.session_b64)
+                }
+            } else {
+                b64_to_session(token)
+            });
         }
     }
 
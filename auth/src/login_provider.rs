@@ -0,0 +1,453 @@
+// Needs `mod login_provider;` adding alongside the crate's other top-level modules once this
+// tree carries a `lib.rs`/`main.rs` to declare them in.
+#![allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace};
+use error_chain::bail;
+use std::sync::Arc;
+use std::path::Path;
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use url::Url;
+
+use ate::prelude::*;
+use ate::error::LoadError;
+use ate::error::TransformError;
+
+use crate::prelude::*;
+use crate::error::*;
+use crate::model::*;
+use crate::helper::*;
+
+/// Everything `process_login` needs out of a successful password/secret check, without it
+/// having to know whether the identity came from this crate's own chain, a config file, or an
+/// LDAP bind. `process_login` still owns the account-status checks, the sudo/TOTP step and
+/// assembling the `LoginResponse` - this only answers "is `secret` valid for `username`, and
+/// if so what keys/session does that unlock".
+#[derive(Debug, Clone)]
+pub struct Credentials
+{
+    pub user_key: PrimaryKey,
+    pub status: UserStatus,
+    pub nominal_read: EncryptKey,
+    pub nominal_write: EncryptKey,
+    pub sudo_read: EncryptKey,
+    pub sudo_write: EncryptKey,
+    pub session: AteSession,
+    /// Present when the account has 2FA enrolled - `process_login` loads this to evaluate the
+    /// supplied TOTP/recovery code before granting the sudo rights above.
+    pub sudo: Option<Sudo>,
+}
+
+/// The subset of [`Credentials`] that can be recovered from an already-derived read key alone
+/// (no secret presented), mirroring what `load_credentials` needs.
+#[derive(Debug, Clone)]
+pub struct PublicCredentials
+{
+    pub user_key: PrimaryKey,
+    pub access: Vec<Authorization>,
+}
+
+/// Authenticates a username/secret pair against an identity source and hands back the crypto
+/// keys and session material `process_login` needs - so `AuthService` can federate against
+/// this crate's own chain, a static config file, an LDAP directory, or anything else, without
+/// `process_login` itself changing.
+#[async_trait]
+pub trait LoginProvider
+where Self: Send + Sync
+{
+    /// Validates `username`/`secret` and returns the keys/session it unlocks.
+    async fn login(&self, username: &str, secret: &EncryptKey) -> Result<Credentials, LoginFailed>;
+
+    /// Validates a device's long-lived API key (provisioned by `register_device_command`)
+    /// instead of an interactive password. Implementations must only ever return the nominal
+    /// read/write rights - an API key is not allowed to unlock the sudo super-super-key path,
+    /// so the returned `Credentials::sudo` is always `None` regardless of what the account has
+    /// enrolled. Providers that have no concept of a provisioned device (e.g. LDAP, where every
+    /// login is a fresh bind) should fail with `LoginFailed::ProviderError`.
+    async fn login_device(&self, email: &str, device_id: &str, api_key: &EncryptKey) -> Result<Credentials, LoginFailed>;
+
+    /// Looks up the read/write keys for `email` without re-presenting a secret, for a caller
+    /// that already holds a session derived from a prior successful login.
+    async fn public_login(&self, email: &str) -> Result<PublicCredentials, LoginFailed>;
+}
+
+/// Builds the `LoginProvider` every `AuthService` uses unless its deployment config selects a
+/// different one (`StaticLoginProvider`/`LdapLoginProvider`): wraps this crate's own chain as
+/// the identity source, the same backend every version of `process_login` used before
+/// `LoginProvider` existed. Nothing in this tree calls it; the `TODO(login-provider)` on
+/// `AuthService::process_login` names the `login_provider` field it fills.
+pub fn default_login_provider(registry: Arc<ate::mesh::Registry>, auth_url: Url, master_session: AteSession) -> Box<dyn LoginProvider>
+{
+    Box::new(ChainLoginProvider::new(registry, auth_url, master_session))
+}
+
+/// The original identity source: a `User` DAO loaded out of the per-user chain keyed by
+/// `chain_key_4hex(email, "redo")`, decrypted with the password-derived `secret` mixed with
+/// the registry's master key (see [`AuthService::compute_super_key`]).
+pub struct ChainLoginProvider
+{
+    registry: Arc<ate::mesh::Registry>,
+    auth_url: Url,
+    master_session: AteSession,
+}
+
+impl ChainLoginProvider
+{
+    pub fn new(registry: Arc<ate::mesh::Registry>, auth_url: Url, master_session: AteSession) -> ChainLoginProvider
+    {
+        ChainLoginProvider { registry, auth_url, master_session }
+    }
+
+    fn compute_super_key(&self, secret: &EncryptKey) -> Option<EncryptKey>
+    {
+        let master_key = match self.master_session.read_keys().next() {
+            Some(a) => a.clone(),
+            None => { return None; }
+        };
+        let super_key = AteHash::from_bytes_twice(master_key.value(), secret.value());
+        Some(EncryptKey::from_seed_bytes(super_key.to_bytes(), KeySize::Bit256))
+    }
+}
+
+#[async_trait]
+impl LoginProvider
+for ChainLoginProvider
+{
+    async fn login(&self, username: &str, secret: &EncryptKey) -> Result<Credentials, LoginFailed>
+    {
+        let super_key = match self.compute_super_key(secret) {
+            Some(a) => a,
+            None => { return Err(LoginFailed::NoMasterKey); }
+        };
+        let super_super_key = match self.compute_super_key(&super_key) {
+            Some(a) => a,
+            None => { return Err(LoginFailed::NoMasterKey); }
+        };
+
+        let chain_key = chain_key_4hex(username, Some("redo"));
+        let chain = self.registry.open(&self.auth_url, &chain_key).await?;
+        let user_key = PrimaryKey::from(username.to_string());
+
+        // Fast path: `verify_blob` is readable under the master session alone (no per-user
+        // read key needed), so a wrong password can be rejected explicitly before ever
+        // attempting the super-key-gated load below - and without conflating "wrong password"
+        // with "this row is genuinely unreadable", the way `MissingReadKey` does.
+        //
+        // `User::verify_blob` is `None` for rows created before `VerifyBlob` existed; the
+        // `None` arm below is the migration path for those rows.
+        let master_dio = chain.dio_full(&self.master_session).await;
+        let mut verify_user = match master_dio.load::<User>(&user_key).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                return Err(LoginFailed::UserNotFound(username.to_string()));
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let needs_migration = match verify_user.verify_blob.as_ref() {
+            Some(blob) => {
+                if blob.verify(&super_key) == false {
+                    return Err(LoginFailed::WrongPassword);
+                }
+                false
+            },
+            None => true,
+        };
+
+        let mut super_session = AteSession::default();
+        super_session.user.add_read_key(&super_key);
+        super_session.user.add_read_key(&super_super_key);
+
+        let dio = chain.dio(&super_session).await;
+        let user = match dio.load::<User>(&user_key).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                return Err(LoginFailed::UserNotFound(username.to_string()));
+            },
+            Err(LoadError(LoadErrorKind::TransformationError(TransformErrorKind::MissingReadKey(_)), _)) => {
+                return Err(LoginFailed::WrongPasswordOrCode);
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        // Migration: this row predates `verify_blob` - the full load above just proved the
+        // password correct the old (ambiguous, slower) way, so mint one now and every
+        // subsequent login for this account takes the fast path above instead.
+        if needs_migration {
+            verify_user.verify_blob = Some(VerifyBlob::new(&super_key));
+            master_dio.commit().await?;
+        }
+
+        let sudo = user.sudo.load().await.ok().flatten().map(|a| a.take());
+
+        let mut session = compute_user_auth(&user);
+        session.user.add_identity(username.to_string());
+
+        Ok(Credentials {
+            user_key,
+            status: user.status.clone(),
+            nominal_read: user.nominal_read.clone(),
+            nominal_write: user.nominal_write.clone(),
+            sudo_read: user.sudo_read.clone(),
+            sudo_write: user.sudo_write.clone(),
+            session,
+            sudo,
+        })
+    }
+
+    async fn login_device(&self, email: &str, device_id: &str, api_key: &EncryptKey) -> Result<Credentials, LoginFailed>
+    {
+        // A device key isn't mixed with the master key the way a password is, so there is no
+        // super-key to derive - the `master_session` used by `public_login` already has enough
+        // read access to load the row; `check_device_api_key` is what actually authenticates.
+        let chain_key = chain_key_4hex(email, Some("redo"));
+        let chain = self.registry.open(&self.auth_url, &chain_key).await?;
+        let dio = chain.dio(&self.master_session).await;
+
+        let user_key = PrimaryKey::from(email.to_string());
+        let user = match dio.load::<User>(&user_key).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                return Err(LoginFailed::UserNotFound(email.to_string()));
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let sudo = match user.sudo.load().await.ok().flatten().map(|a| a.take()) {
+            Some(a) => a,
+            None => { return Err(LoginFailed::WrongPasswordOrCode); }
+        };
+        if sudo.check_device_api_key(device_id, api_key) == false {
+            return Err(LoginFailed::WrongPasswordOrCode);
+        }
+
+        let mut session = compute_user_auth(&user);
+        session.user.add_identity(email.to_string());
+
+        Ok(Credentials {
+            user_key,
+            status: user.status.clone(),
+            nominal_read: user.nominal_read.clone(),
+            nominal_write: user.nominal_write.clone(),
+            sudo_read: user.sudo_read.clone(),
+            sudo_write: user.sudo_write.clone(),
+            session,
+            // An API key never unlocks the sudo super-super-key path, regardless of whether
+            // the account has 2FA enrolled.
+            sudo: None,
+        })
+    }
+
+    async fn public_login(&self, email: &str) -> Result<PublicCredentials, LoginFailed>
+    {
+        let chain_key = chain_key_4hex(email, Some("redo"));
+        let chain = self.registry.open(&self.auth_url, &chain_key).await?;
+        let dio = chain.dio(&self.master_session).await;
+
+        let user_key = PrimaryKey::from(email.to_string());
+        let user = match dio.load::<User>(&user_key).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                return Err(LoginFailed::UserNotFound(email.to_string()));
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        Ok(PublicCredentials {
+            user_key,
+            access: user.access.clone(),
+        })
+    }
+}
+
+/// A single entry in a [`StaticLoginProvider`]'s user list - loaded once from a JSON file
+/// on disk rather than a chain, for small deployments that don't want to stand up the full
+/// mesh just to authenticate a handful of accounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaticLoginEntry
+{
+    pub email: String,
+    pub nominal_read: EncryptKey,
+    pub nominal_write: EncryptKey,
+    pub sudo_read: EncryptKey,
+    pub sudo_write: EncryptKey,
+}
+
+/// A fixed, file-backed identity source - the whole user list is read once at startup from
+/// `path` and matched by email; `secret` is compared against `nominal_read` directly rather
+/// than unwrapped from a chain. Intended for small/offline deployments, not as a replacement
+/// for the chain-backed provider's per-account locking and 2FA support.
+pub struct StaticLoginProvider
+{
+    users: Vec<StaticLoginEntry>,
+}
+
+impl StaticLoginProvider
+{
+    pub async fn load(path: &Path) -> Result<StaticLoginProvider, LoginError>
+    {
+        let raw = tokio::fs::read_to_string(path).await?;
+        let users: Vec<StaticLoginEntry> = serde_json::from_str(&raw)?;
+        Ok(StaticLoginProvider { users })
+    }
+}
+
+#[async_trait]
+impl LoginProvider
+for StaticLoginProvider
+{
+    async fn login(&self, username: &str, secret: &EncryptKey) -> Result<Credentials, LoginFailed>
+    {
+        let entry = self.users.iter()
+            .find(|u| u.email == username)
+            .ok_or_else(|| LoginFailed::UserNotFound(username.to_string()))?;
+
+        if entry.nominal_read.value() != secret.value() {
+            return Err(LoginFailed::WrongPasswordOrCode);
+        }
+
+        let mut session = AteSession::default();
+        session.user.add_read_key(&entry.nominal_read);
+        session.user.add_write_key(&entry.nominal_write);
+        session.user.add_identity(username.to_string());
+
+        Ok(Credentials {
+            user_key: PrimaryKey::from(username.to_string()),
+            status: UserStatus::Nominal,
+            nominal_read: entry.nominal_read.clone(),
+            nominal_write: entry.nominal_write.clone(),
+            sudo_read: entry.sudo_read.clone(),
+            sudo_write: entry.sudo_write.clone(),
+            session,
+            sudo: None,
+        })
+    }
+
+    async fn login_device(&self, _email: &str, _device_id: &str, _api_key: &EncryptKey) -> Result<Credentials, LoginFailed>
+    {
+        // The static user list has no per-device record to provision a key onto - only the
+        // chain-backed provider (where `register_device_command` has somewhere to persist
+        // a `DeviceApiKey`) supports device login today.
+        Err(LoginFailed::ProviderError("the static provider does not support device API keys".to_string()))
+    }
+
+    async fn public_login(&self, email: &str) -> Result<PublicCredentials, LoginFailed>
+    {
+        let entry = self.users.iter()
+            .find(|u| u.email == email)
+            .ok_or_else(|| LoginFailed::UserNotFound(email.to_string()))?;
+
+        Ok(PublicCredentials {
+            user_key: PrimaryKey::from(email.to_string()),
+            access: vec![Authorization {
+                read: entry.nominal_read.clone(),
+                write: entry.nominal_write.clone(),
+            }],
+        })
+    }
+}
+
+/// Federates authentication to an existing corporate directory: `username`/`secret` are used
+/// to bind against `url`, and on a successful bind the entry's `read_key_attr`/`write_key_attr`
+/// attributes (provisioned out-of-band, e.g. by an LDAP schema extension) are mapped onto the
+/// keys [`compute_user_auth`] would otherwise derive from a locally-stored `User` DAO.
+pub struct LdapLoginProvider
+{
+    url: String,
+    base_dn: String,
+    read_key_attr: String,
+    write_key_attr: String,
+}
+
+impl LdapLoginProvider
+{
+    pub fn new(url: String, base_dn: String, read_key_attr: String, write_key_attr: String) -> LdapLoginProvider
+    {
+        LdapLoginProvider { url, base_dn, read_key_attr, write_key_attr }
+    }
+
+    fn bind_dn(&self, username: &str) -> String
+    {
+        format!("uid={},{}", username, self.base_dn)
+    }
+}
+
+#[async_trait]
+impl LoginProvider
+for LdapLoginProvider
+{
+    async fn login(&self, username: &str, secret: &EncryptKey) -> Result<Credentials, LoginFailed>
+    {
+        // `secret` here is the plaintext-equivalent bind credential (unlike the chain provider,
+        // an LDAP bind is the authentication step itself, so there is no password-derived read
+        // key to decrypt a DAO with - the directory entry's key attributes are trusted once the
+        // bind succeeds).
+        let (conn, mut ldap) = ldap3::LdapConnAsync::new(self.url.as_str())
+            .await
+            .map_err(|err| LoginFailed::ProviderError(err.to_string()))?;
+        ldap3::drive!(conn);
+
+        let bind_dn = self.bind_dn(username);
+        let password = String::from_utf8_lossy(secret.value()).to_string();
+        ldap.simple_bind(bind_dn.as_str(), password.as_str())
+            .await
+            .map_err(|err| LoginFailed::ProviderError(err.to_string()))?
+            .success()
+            .map_err(|_| LoginFailed::WrongPasswordOrCode)?;
+
+        let (results, _) = ldap.search(
+            bind_dn.as_str(),
+            ldap3::Scope::Base,
+            "(objectClass=*)",
+            vec![self.read_key_attr.as_str(), self.write_key_attr.as_str()],
+        )
+        .await
+        .map_err(|err| LoginFailed::ProviderError(err.to_string()))?
+        .success()
+        .map_err(|err| LoginFailed::ProviderError(err.to_string()))?;
+
+        let entry = results.into_iter().next()
+            .ok_or_else(|| LoginFailed::UserNotFound(username.to_string()))?;
+        let entry = ldap3::SearchEntry::construct(entry);
+
+        let read_bytes = entry.attrs.get(&self.read_key_attr)
+            .and_then(|v| v.first())
+            .ok_or_else(|| LoginFailed::ProviderError("directory entry is missing its read-key attribute".to_string()))?;
+        let write_bytes = entry.attrs.get(&self.write_key_attr)
+            .and_then(|v| v.first())
+            .ok_or_else(|| LoginFailed::ProviderError("directory entry is missing its write-key attribute".to_string()))?;
+
+        let nominal_read = EncryptKey::from_seed_bytes(AteHash::from_bytes(read_bytes.as_bytes()).to_bytes(), KeySize::Bit256);
+        let nominal_write = EncryptKey::from_seed_bytes(AteHash::from_bytes(write_bytes.as_bytes()).to_bytes(), KeySize::Bit256);
+
+        let mut session = AteSession::default();
+        session.user.add_read_key(&nominal_read);
+        session.user.add_write_key(&nominal_write);
+        session.user.add_identity(username.to_string());
+
+        Ok(Credentials {
+            user_key: PrimaryKey::from(username.to_string()),
+            status: UserStatus::Nominal,
+            nominal_read: nominal_read.clone(),
+            nominal_write: nominal_write.clone(),
+            sudo_read: nominal_read,
+            sudo_write: nominal_write,
+            session,
+            sudo: None,
+        })
+    }
+
+    async fn login_device(&self, _email: &str, _device_id: &str, _api_key: &EncryptKey) -> Result<Credentials, LoginFailed>
+    {
+        // The directory is the source of truth for credentials; there is no local record to
+        // hang a provisioned device key off, so device login isn't meaningful against LDAP.
+        Err(LoginFailed::ProviderError("the LDAP provider does not support device API keys".to_string()))
+    }
+
+    async fn public_login(&self, _email: &str) -> Result<PublicCredentials, LoginFailed>
+    {
+        // LDAP has no offline "already proved it once" path - every lookup is a fresh bind, so
+        // there is nothing this can answer without the secret `login` requires.
+        Err(LoginFailed::ProviderError("the LDAP provider requires a fresh bind; public_login is not supported".to_string()))
+    }
+}
@@ -0,0 +1,72 @@
+// Needs `mod oauth_state;` adding alongside the crate's other model modules once this tree
+// carries the `model/mod.rs` that declares them.
+#![allow(unused_imports)]
+use serde::{Serialize, Deserialize};
+
+use super::*;
+
+/// How long a begun-but-not-completed OAuth login is allowed to sit waiting for the user to
+/// finish the provider's consent screen before `process_oauth_complete` refuses it outright.
+pub const OAUTH_STATE_TTL_SECS: u64 = 10 * 60;
+
+/// The server-side half of a single in-flight OAuth2 authorization-code exchange, keyed by its
+/// own `state` value rather than any account - a flow often starts before the server knows
+/// which (if any) `User` it will resolve to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthState
+{
+    pub state: String,
+    /// Which configured provider (`"google"`, `"github"`, an OIDC issuer name, ...) this
+    /// exchange is against, so `process_oauth_complete` knows which token/userinfo endpoints
+    /// and client credentials to use.
+    pub provider: String,
+    /// The PKCE code verifier generated in `process_oauth_begin`; sent back to the provider's
+    /// token endpoint in `process_oauth_complete` so a stolen authorization code alone is
+    /// useless without it.
+    pub code_verifier: String,
+    pub created_at: u64,
+}
+
+impl OAuthState
+{
+    fn is_expired(&self, now: u64) -> bool {
+        now >= self.created_at + OAUTH_STATE_TTL_SECS
+    }
+}
+
+/// The single well-known row every in-flight OAuth exchange is recorded against, living in the
+/// command chain (see `AuthService::process_oauth_begin`) rather than on any `User` -
+/// mirroring how `Sudo` tracks its own `RefreshToken`/`DeviceApiKey` entries as a plain `Vec`
+/// rather than one row per token.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OAuthStateStore
+{
+    pending: Vec<OAuthState>,
+}
+
+impl OAuthStateStore
+{
+    /// Mints a fresh `state`/PKCE `code_verifier` pair for `provider` and records it so a
+    /// matching `process_oauth_complete` call can find it again.
+    pub fn begin(&mut self, provider: String, now: u64) -> OAuthState {
+        self.pending.retain(|s| s.is_expired(now) == false);
+
+        let state = OAuthState {
+            state: random_hex_string(32),
+            provider,
+            code_verifier: random_hex_string(64),
+            created_at: now,
+        };
+        self.pending.push(state.clone());
+        state
+    }
+
+    /// Single-use: removes and returns the matching entry if `state` is on file and not
+    /// expired, so each `state` is accepted at most once; a replayed or forged value (or one
+    /// whose window has lapsed) gets `None`.
+    pub fn consume(&mut self, state: &str, now: u64) -> Option<OAuthState> {
+        self.pending.retain(|s| s.is_expired(now) == false);
+        let idx = self.pending.iter().position(|s| s.state == state)?;
+        Some(self.pending.remove(idx))
+    }
+}
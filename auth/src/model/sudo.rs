@@ -1,17 +1,459 @@
 #[allow(unused_imports)]
 use tracing::{info, warn, debug, error, trace, instrument, span, Level};
 use serde::*;
+use rand::Rng;
+use zeroize::Zeroize;
+use sha2::{Sha256, Digest};
+use ring::signature::{self, UnparsedPublicKey};
 
 use super::*;
 
+/// A `String` that wipes itself on drop and never prints its contents through `Debug`, used
+/// for the TOTP seed so it can't linger in freed heap pages or leak through a `tracing` call
+/// that happens to log a `Sudo` record.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: String) -> SecretString {
+        SecretString(value)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl std::ops::Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecretString(\"***\")")
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> SecretString {
+        SecretString(value)
+    }
+}
+
+impl Zeroize for SecretString {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// How many backup codes are minted by [`Sudo::generate_recovery_codes`].
+const DEFAULT_RECOVERY_CODE_COUNT: usize = 10;
+
+/// Failed attempts at or below this threshold are not throttled at all - only once an attacker
+/// has had a few free guesses does the exponential backoff kick in.
+const LOCKOUT_THRESHOLD: u32 = 3;
+/// Base lockout window, doubled for every attempt past [`LOCKOUT_THRESHOLD`].
+const LOCKOUT_BASE_SECS: u64 = 2;
+/// Lockout windows never grow past this, so a persistent attacker is throttled but an account
+/// is never bricked for longer than an hour.
+const LOCKOUT_MAX_SECS: u64 = 3600;
+
+/// Default lifetime of a freshly minted refresh token, used by `process_renew` when it rotates
+/// the token it consumed.
+pub const REFRESH_TOKEN_TTL_SECS: u64 = 24 * 3600;
+
+/// A rotating credential that lets `process_renew` mint a fresh short-lived session without a
+/// password or TOTP code. Only the hash is stored - like a [`ScopedGrant`], the plaintext is
+/// visible only at the moment it's minted. Single-use: [`Sudo::consume_refresh_token`] removes
+/// the matching entry so a captured refresh token can't be replayed after its first use, and
+/// every renewal is expected to mint (and return) a new one to keep the chain of renewals
+/// unbroken.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshToken {
+    token_hash: String,
+    expires_at: u64,
+}
+
+/// A single-use TOTP recovery code, stored as a salted hash so the plaintext never touches
+/// disk - only [`Sudo::generate_recovery_codes`] ever sees the value in the clear.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecoveryCode {
+    salt: String,
+    hash: String,
+}
+
+impl RecoveryCode {
+    fn new(code: &str) -> (RecoveryCode, String) {
+        let salt = random_hex_string(16);
+        let hash = hash_recovery_code(&salt, code);
+        (RecoveryCode { salt, hash }, code.to_string())
+    }
+
+    fn matches(&self, code: &str) -> bool {
+        self.hash.is_empty() == false && hash_recovery_code(&self.salt, code) == self.hash
+    }
+}
+
+fn hash_recovery_code(salt: &str, code: &str) -> String {
+    AteHash::from_bytes_twice(salt.as_bytes(), code.as_bytes()).to_hex_string()
+}
+
+fn random_hex_string(bytes: usize) -> String {
+    let mut rng = rand::thread_rng();
+    let mut buf = vec![0u8; bytes];
+    rng.fill(buf.as_mut_slice());
+    buf.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hash_token(token: &str) -> String {
+    // The token itself is already high-entropy random bytes, so (unlike the recovery codes)
+    // no per-token salt is needed to make the stored hash resistant to offline guessing.
+    AteHash::from_bytes(token.as_bytes()).to_hex_string()
+}
+
+fn hash_key_bytes(key: &[u8]) -> String {
+    AteHash::from_bytes(key).to_hex_string()
+}
+
+fn random_token() -> String {
+    random_hex_string(32)
+}
+
+fn random_recovery_code() -> String {
+    const CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+    let mut rng = rand::thread_rng();
+    let group = |rng: &mut rand::rngs::ThreadRng| -> String {
+        (0..5).map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char).collect()
+    };
+    format!("{}-{}", group(&mut rng), group(&mut rng))
+}
+
+/// A narrow permission a [`ScopedGrant`] can carry, mirroring the crate's real operations
+/// rather than a generic read/write split so a minted token can be scoped down to exactly
+/// what an automation needs.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    #[serde(rename = "read_chains")]
+    ReadChains,
+    #[serde(rename = "write_chains")]
+    WriteChains,
+    #[serde(rename = "manage_groups")]
+    ManageGroups,
+    #[serde(rename = "sudo")]
+    Sudo,
+}
+
+/// A bearer token minted for automation, scoped down to a handful of [`Scope`]s instead of
+/// handing out the full `Sudo` session. Only the token's hash is stored - [`Sudo::issue_grant`]
+/// is the one place the plaintext bearer token is ever visible.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScopedGrant {
+    token_hash: String,
+    pub scopes: Vec<Scope>,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub label: String,
+}
+
+/// A long-lived API key provisioned for a single device by `register_device_command`, hung off
+/// the `Sudo` record so a script or long-running service can log in without a human re-typing a
+/// password/TOTP code every time. Only the hash is kept - [`Sudo::issue_device_api_key`] is the
+/// one place the plaintext key is ever visible. Deliberately carries no second-factor or grant
+/// scopes of its own: [`Sudo::check_device_api_key`] only ever feeds the nominal read/write keys
+/// `process_login` assembles, never the sudo super-super-key path a TOTP/recovery code unlocks.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceApiKey {
+    pub device_id: String,
+    key_hash: String,
+    pub issued_at: u64,
+}
+
+/// One enrolled second factor. A `Sudo` can hold several of these at once (e.g. a phone's TOTP
+/// app plus a couple of hardware keys); login succeeds if any one of them validates.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum SecondFactor {
+    /// The original phone-authenticator based factor.
+    Totp {
+        google_auth: String,
+        secret: SecretString,
+        qr_code: String,
+    },
+    /// A FIDO2/WebAuthn hardware authenticator. `sign_count` is the counter value observed on
+    /// the last successful assertion, used to detect cloned authenticators.
+    WebAuthn {
+        credential_id: String,
+        public_key: Vec<u8>,
+        sign_count: u32,
+    },
+}
+
+impl Zeroize for SecondFactor {
+    fn zeroize(&mut self) {
+        if let SecondFactor::Totp { google_auth, secret, qr_code } = self {
+            google_auth.zeroize();
+            secret.zeroize();
+            qr_code.zeroize();
+        }
+    }
+}
+
+/// Parses the big-endian signature counter out of a WebAuthn `authenticatorData` blob
+/// (`rpIdHash[32] ++ flags[1] ++ signCount[4] ++ ...`).
+fn parse_sign_count(authenticator_data: &[u8]) -> Option<u32> {
+    let bytes = authenticator_data.get(33..37)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Sudo {
     pub email: String,
     pub uid: u32,
-    pub google_auth: String,
-    pub secret: String,
-    pub qr_code: String,
     pub failed_attempts: u32,
     pub access: Vec<Authorization>,
     pub groups: Vec<String>,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub second_factors: Vec<SecondFactor>,
+    #[serde(default)]
+    pub recovery_codes: Vec<RecoveryCode>,
+    #[serde(default)]
+    pub locked_until: Option<u64>,
+    #[serde(default)]
+    pub grants: Vec<ScopedGrant>,
+    #[serde(default)]
+    pub device_keys: Vec<DeviceApiKey>,
+    #[serde(default)]
+    pub refresh_tokens: Vec<RefreshToken>,
+}
+
+impl Sudo {
+    /// Mints a fresh set of backup codes, returning the plaintext so the caller can show it to
+    /// the user exactly once. Replaces (invalidates) any codes generated previously.
+    ///
+    /// TODO(recovery-codes): the command that first provisions a `Totp` factor (rendering the
+    /// enrollment QR code) should call this in the same step and display both together - that
+    /// enrollment command lives outside `model/`, alongside the rest of `crate::commands`,
+    /// which this tree doesn't carry. `regenerate_recovery_codes_command` covers re-minting a
+    /// lost set after enrollment; it doesn't cover the initial mint.
+    pub fn generate_recovery_codes(&mut self) -> Vec<String> {
+        self.generate_recovery_codes_n(DEFAULT_RECOVERY_CODE_COUNT)
+    }
+
+    pub fn generate_recovery_codes_n(&mut self, count: usize) -> Vec<String> {
+        let mut plaintext = Vec::with_capacity(count);
+        let mut stored = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let (entry, code) = RecoveryCode::new(&random_recovery_code());
+            stored.push(entry);
+            plaintext.push(code);
+        }
+
+        self.recovery_codes = stored;
+        plaintext
+    }
+
+    /// Invalidates every outstanding recovery code and mints a fresh batch.
+    pub fn regenerate_recovery_codes(&mut self) -> Vec<String> {
+        self.generate_recovery_codes()
+    }
+
+    /// Checks `code` against the stored hashes. A matching entry is blanked so it can never be
+    /// used again, making each backup code single-use.
+    pub fn verify_recovery_code(&mut self, code: &str) -> bool {
+        for entry in self.recovery_codes.iter_mut() {
+            if entry.matches(code) {
+                entry.salt.clear();
+                entry.hash.clear();
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Records a failed TOTP/credential check. Once `failed_attempts` passes
+    /// [`LOCKOUT_THRESHOLD`], each further failure doubles the lockout window (capped at
+    /// [`LOCKOUT_MAX_SECS`]) instead of leaving the account open to unlimited guessing.
+    pub fn register_failure(&mut self, now: u64) {
+        self.failed_attempts += 1;
+
+        if self.failed_attempts > LOCKOUT_THRESHOLD {
+            let exponent = (self.failed_attempts - LOCKOUT_THRESHOLD - 1).min(20);
+            let delay = LOCKOUT_BASE_SECS.saturating_mul(1u64 << exponent).min(LOCKOUT_MAX_SECS);
+            self.locked_until = Some(now + delay);
+        }
+    }
+
+    /// Clears the failure count and any active lockout after a successful auth.
+    pub fn register_success(&mut self) {
+        self.failed_attempts = 0;
+        self.locked_until = None;
+    }
+
+    /// Returns the number of seconds left on an active lockout, or `None` if the account is
+    /// not currently locked.
+    pub fn lockout_remaining(&self, now: u64) -> Option<u64> {
+        match self.locked_until {
+            Some(until) if until > now => Some(until - now),
+            _ => None,
+        }
+    }
+
+    /// Mints a new bearer token scoped to `scopes`, valid for `ttl_secs` from `now`. Returns
+    /// the plaintext token - only its hash is kept on the `Sudo` record.
+    pub fn issue_grant(&mut self, scopes: Vec<Scope>, ttl_secs: u64, label: String, now: u64) -> String {
+        let token = random_token();
+        self.grants.push(ScopedGrant {
+            token_hash: hash_token(&token),
+            scopes,
+            issued_at: now,
+            expires_at: now + ttl_secs,
+            label,
+        });
+        token
+    }
+
+    /// Checks that `token` is a live (unexpired) grant carrying `required_scope`.
+    pub fn check_grant(&self, token: &str, required_scope: Scope, now: u64) -> bool {
+        let hash = hash_token(token);
+        self.grants.iter().any(|grant| {
+            grant.token_hash == hash
+                && grant.expires_at > now
+                && grant.scopes.contains(&required_scope)
+        })
+    }
+
+    /// Revokes every grant matching `token`, e.g. for an explicit "sign out this automation"
+    /// action.
+    pub fn revoke_grant(&mut self, token: &str) {
+        let hash = hash_token(token);
+        self.grants.retain(|grant| grant.token_hash != hash);
+    }
+
+    /// Mints a fresh API key for `device_id`, returning the plaintext key so the caller can
+    /// write it to its token file - only the hash is kept here. Replaces any key already
+    /// issued to that same `device_id`, so re-running `register_device_command` on a machine
+    /// rotates its key rather than accumulating stale ones.
+    pub fn issue_device_api_key(&mut self, device_id: String, now: u64) -> EncryptKey {
+        self.revoke_device_api_key(&device_id);
+        let key = EncryptKey::generate(KeySize::Bit256);
+        self.device_keys.push(DeviceApiKey {
+            device_id,
+            key_hash: hash_key_bytes(key.value()),
+            issued_at: now,
+        });
+        key
+    }
+
+    /// Checks `key` against the stored hash for `device_id`.
+    pub fn check_device_api_key(&self, device_id: &str, key: &EncryptKey) -> bool {
+        let hash = hash_key_bytes(key.value());
+        self.device_keys.iter().any(|k| k.device_id == device_id && k.key_hash == hash)
+    }
+
+    /// Revokes the API key issued to `device_id`, e.g. when that device is decommissioned.
+    pub fn revoke_device_api_key(&mut self, device_id: &str) {
+        self.device_keys.retain(|k| k.device_id != device_id);
+    }
+
+    /// Mints a fresh refresh token valid for `ttl_secs` from `now`, returning the plaintext
+    /// token and its expiry. Does not invalidate any other outstanding refresh token - a user
+    /// may have several devices/terminals, each holding its own renewable session.
+    pub fn issue_refresh_token(&mut self, ttl_secs: u64, now: u64) -> (String, u64) {
+        let token = random_token();
+        let expires_at = now + ttl_secs;
+        self.refresh_tokens.push(RefreshToken {
+            token_hash: hash_token(&token),
+            expires_at,
+        });
+        (token, expires_at)
+    }
+
+    /// Validates `token` and, if it is a live (unexpired) refresh token, consumes it so it
+    /// can't be replayed - the caller is expected to immediately mint a new one via
+    /// [`Sudo::issue_refresh_token`] to keep the session renewable.
+    pub fn consume_refresh_token(&mut self, token: &str, now: u64) -> bool {
+        let hash = hash_token(token);
+        match self.refresh_tokens.iter().position(|t| t.token_hash == hash && t.expires_at > now) {
+            Some(index) => {
+                self.refresh_tokens.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Checks `code` against every enrolled `Totp` factor.
+    pub fn verify_totp(&self, code: &str, time: u64) -> bool {
+        let google_auth = google_authenticator::GoogleAuthenticator::new();
+        self.second_factors.iter().any(|factor| match factor {
+            SecondFactor::Totp { secret, .. } => google_auth.verify_code(secret.as_str(), code, 3, time),
+            _ => false,
+        })
+    }
+
+    /// Verifies a FIDO2 assertion against the enrolled `WebAuthn` factor matching
+    /// `credential_id`: the authenticator's signature must cover
+    /// `authenticator_data ++ SHA-256(client_data_json)`, and the counter it reports must be
+    /// strictly greater than the last one observed (a non-increasing counter means the
+    /// authenticator's key material may have been cloned). On success, the stored `sign_count`
+    /// is advanced so the next assertion is checked against it in turn.
+    pub fn verify_webauthn(&mut self, credential_id: &str, authenticator_data: &[u8], client_data_json: &[u8], signature_bytes: &[u8]) -> bool {
+        let reported_sign_count = match parse_sign_count(authenticator_data) {
+            Some(count) => count,
+            None => return false,
+        };
+
+        let factor = self.second_factors.iter_mut().find_map(|factor| match factor {
+            SecondFactor::WebAuthn { credential_id: id, public_key, sign_count } if id == credential_id => {
+                Some((public_key, sign_count))
+            },
+            _ => None,
+        });
+
+        let (public_key, sign_count) = match factor {
+            Some(a) => a,
+            None => return false,
+        };
+
+        if reported_sign_count <= *sign_count {
+            return false;
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(client_data_json);
+        let client_data_hash = hasher.finalize();
+
+        let mut signed_data = Vec::with_capacity(authenticator_data.len() + client_data_hash.len());
+        signed_data.extend_from_slice(authenticator_data);
+        signed_data.extend_from_slice(&client_data_hash);
+
+        let verifier = UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, public_key.as_slice());
+        if verifier.verify(&signed_data, signature_bytes).is_err() {
+            return false;
+        }
+
+        *sign_count = reported_sign_count;
+        true
+    }
+}
+
+// Only the `Totp` factors carry secret material worth wiping; `WebAuthn` factors store a
+// public key and counter, neither of which is sensitive.
+impl Drop for Sudo {
+    fn drop(&mut self) {
+        for factor in self.second_factors.iter_mut() {
+            factor.zeroize();
+        }
+    }
+}
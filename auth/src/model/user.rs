@@ -0,0 +1,54 @@
+// Needs `mod user;` adding alongside the crate's other model modules once this tree carries
+// the `model/mod.rs` that declares them.
+#![allow(unused_imports)]
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use super::*;
+
+/// Whether an account can be logged into right now - checked by `process_login` before it even
+/// looks at the supplied secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum UserStatus
+{
+    Nominal,
+    /// `process_login` refuses the account until `until`, and ignores this status once `until`
+    /// has passed. Distinct from `Sudo::locked_until`, which `Sudo::register_failure` sets.
+    Locked(DateTime<Utc>),
+    /// Set on account creation until the registration flow's email-verification step clears it;
+    /// `process_login` refuses to proceed at all while this holds, password or code
+    /// notwithstanding.
+    Unverified,
+}
+
+/// One read/write key pair a `User` grants access through - `nominal_read`/`nominal_write` and
+/// `sudo_read`/`sudo_write` are really just the first two entries of what in principle could be
+/// a list, which is what `access` is for (e.g. service accounts or shared rows with more than
+/// one way in).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Authorization
+{
+    pub read: EncryptKey,
+    pub write: EncryptKey,
+}
+
+/// The account row every `LoginProvider` ultimately authenticates against (bar
+/// `StaticLoginProvider`/`LdapLoginProvider`, which have their own out-of-band identity
+/// sources), keyed by `chain_key_4hex(email, "redo")` and loaded with `PrimaryKey::from(email)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User
+{
+    pub status: UserStatus,
+    pub nominal_read: EncryptKey,
+    pub nominal_write: EncryptKey,
+    pub sudo_read: EncryptKey,
+    pub sudo_write: EncryptKey,
+    pub access: Vec<Authorization>,
+    /// The account's 2FA/recovery-code/device-key record, kept as a separate child row rather
+    /// than inline - see `Sudo` itself and `ChainLoginProvider::login`'s `sudo.load()` call.
+    pub sudo: DaoRef<Sudo>,
+    /// An explicit, fast password check readable under the master session alone - see
+    /// `VerifyBlob` and the migration path in `ChainLoginProvider::login`. `None` for rows
+    /// created before `VerifyBlob` existed, until the next successful login mints one.
+    pub verify_blob: Option<VerifyBlob>,
+}
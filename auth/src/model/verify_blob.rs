@@ -0,0 +1,63 @@
+// Needs `mod verify_blob;` adding alongside the crate's other model modules (`sudo`, etc.) once
+// this tree carries the `model/mod.rs` that declares them.
+#![allow(unused_imports)]
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chacha20poly1305::aead::{Aead, NewAead};
+
+use super::*;
+
+/// The fixed plaintext a [`VerifyBlob`] encrypts - its value doesn't matter, only that
+/// decrypting it under the candidate super-key reproduces exactly these bytes.
+const VERIFY_CONSTANT: &[u8] = b"ate-verify-blob-v1";
+
+/// An explicit, fast password check: a known constant encrypted under the account's
+/// super-key, stored on the `User` row and readable under the master session alone (no
+/// per-user read key required). Tells "the password is wrong" apart from "this row is
+/// genuinely unreadable by anyone" - a distinction `TransformErrorKind::MissingReadKey` can't
+/// make, since that variant fires for both.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerifyBlob {
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+impl VerifyBlob {
+    /// Encrypts [`VERIFY_CONSTANT`] under `super_key`. Called at account creation, or lazily
+    /// by `ChainLoginProvider::login`'s migration path the next time an existing account (one
+    /// with no `verify_blob` yet) logs in successfully.
+    pub fn new(super_key: &EncryptKey) -> VerifyBlob {
+        let mut rng = rand::thread_rng();
+        let mut nonce_bytes = [0u8; 12];
+        rng.fill(&mut nonce_bytes);
+
+        let cipher = Self::cipher(super_key);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+        let ciphertext = cipher.encrypt(nonce, VERIFY_CONSTANT)
+            .expect("chacha20poly1305 encryption of the verify constant must not fail");
+
+        VerifyBlob { nonce: nonce_bytes, ciphertext }
+    }
+
+    /// Returns `true` only if `candidate_key` decrypts this blob back to [`VERIFY_CONSTANT`] -
+    /// a fast, explicit "is this the right password" check that doesn't require loading the
+    /// `User`/`Sudo` DAOs at all.
+    pub fn verify(&self, candidate_key: &EncryptKey) -> bool {
+        let cipher = Self::cipher(candidate_key);
+        let nonce = Nonce::from_slice(&self.nonce);
+        match cipher.decrypt(nonce, &self.ciphertext[..]) {
+            Ok(plaintext) => plaintext == VERIFY_CONSTANT,
+            Err(_) => false,
+        }
+    }
+
+    fn cipher(key: &EncryptKey) -> ChaCha20Poly1305 {
+        // Domain-separated from the key's other uses (e.g. wrapping the row's read keys) so
+        // this blob can't be confused with, or substituted for, any other ciphertext under
+        // the same super-key.
+        let derived = AteHash::from_bytes_twice(key.value(), b"ate-verify-blob-aead");
+        let key = Key::from_slice(&derived.to_bytes()[..32]);
+        ChaCha20Poly1305::new(key)
+    }
+}
@@ -0,0 +1,136 @@
+// Needs `mod request;` adding alongside the crate's other top-level modules once this tree
+// carries a `lib.rs`/`main.rs` to declare them in - see the `login_provider`/`oauth_provider`
+// modules for the same situation.
+#![allow(unused_imports)]
+use serde::{Serialize, Deserialize};
+
+use ate::prelude::*;
+
+/// Provisions a long-lived device API key onto an already-authenticated account, so a
+/// non-interactive client can log back in without a password or TOTP code every time - see
+/// `AuthService::process_register_device` and `register_device_command`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterDeviceRequest
+{
+    /// Proof the caller already completed a full (password + TOTP/recovery code) login - a
+    /// device key is minted onto an existing account, never used to bootstrap one.
+    pub session: AteSession,
+    pub email: String,
+    /// Stable for the life of the resulting token file; re-registering with the same
+    /// `device_id` rotates the key rather than piling up an ever-growing list of them.
+    pub device_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterDeviceResponse
+{
+    pub device_id: String,
+    pub api_key: EncryptKey,
+}
+
+/// Why `AuthService::process_register_device` refused to provision a device key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RegisterDeviceFailed
+{
+    UserNotFound(String),
+    /// The account has no `Sudo` record to hang a device key off.
+    NoSudo(String),
+    /// The master session combined with the caller's session can't decrypt the account's
+    /// `User` row, or its `Sudo` row (the case when the caller's session lacks sudo rights).
+    NoMasterKey,
+}
+
+/// Exchanges a still-live refresh token for a fresh session, no password or TOTP code
+/// required - see `AuthService::process_renew` and `renew_command`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenewRequest
+{
+    pub email: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenewResponse
+{
+    pub authority: AteSession,
+    pub expires_at: u64,
+    /// Single-use: a fresh one, replacing the one just consumed, so the client can keep
+    /// renewing indefinitely without ever re-entering a password.
+    pub refresh_token: String,
+}
+
+/// Why `AuthService::process_renew` refused to renew a session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RenewFailed
+{
+    UserNotFound(String),
+    /// `refresh_token` doesn't match any token on file for this account, or has expired.
+    InvalidToken,
+}
+
+/// Starts an OAuth2/OIDC authorization-code flow against `provider` - see
+/// `AuthService::process_oauth_begin` and `oauth_login_command`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthBeginRequest
+{
+    pub provider: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthBeginResponse
+{
+    /// The URL the client should open in a browser to reach the provider's consent screen.
+    pub authorize_url: String,
+    /// Compared by `oauth_login_command` against the redirect's own `state` query parameter,
+    /// so a spoofed or stale redirect is rejected before `process_oauth_complete` is called.
+    pub state: String,
+}
+
+/// Completes a flow begun by `OAuthBeginRequest` - see `AuthService::process_oauth_complete`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthCompleteRequest
+{
+    pub state: String,
+    pub code: String,
+}
+
+/// Why an OAuth login attempt (either half) was refused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OAuthLoginFailed
+{
+    UnknownProvider(String),
+    /// `state` is unknown, already consumed, expired, or didn't match the redirect's own value.
+    InvalidState,
+    /// The provider's token/userinfo endpoints returned something `exchange_code` couldn't
+    /// make sense of, or the exchange request itself failed.
+    ProviderError(String),
+    NoMasterKey,
+}
+
+/// Replaces an account's recovery codes with a fresh set - see
+/// `AuthService::process_regenerate_recovery_codes` and `regenerate_recovery_codes_command`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegenerateRecoveryCodesRequest
+{
+    /// Must carry the account's sudo read key (proven by a prior password + TOTP/recovery-code
+    /// login) - a merely nominal session is rejected with `SudoRequired`.
+    pub session: AteSession,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegenerateRecoveryCodesResponse
+{
+    /// Shown to the caller exactly once - the server never hands these back again.
+    pub recovery_codes: Vec<String>,
+}
+
+/// Why `AuthService::process_regenerate_recovery_codes` refused to mint new recovery codes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RegenerateRecoveryCodesFailed
+{
+    UserNotFound(String),
+    NoSudo(String),
+    /// The caller's session didn't carry the account's sudo read key.
+    SudoRequired,
+}
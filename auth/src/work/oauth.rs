@@ -0,0 +1,171 @@
+#![allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use error_chain::bail;
+use std::sync::Arc;
+use url::Url;
+
+use ate::prelude::*;
+use ate::error::LoadError;
+use ate::error::TransformError;
+
+use crate::prelude::*;
+use crate::request::*;
+use crate::service::AuthService;
+use crate::helper::*;
+use crate::error::*;
+use crate::model::*;
+use crate::oauth_provider::OAuthProviderConfig;
+
+/// The single well-known row `OAuthStateStore` is kept under within the command chain -
+/// distinct from any user's own chain (keyed by `chain_key_4hex(email, "redo")`), since a
+/// pending exchange belongs to the login attempt itself and often predates knowing which
+/// account it resolves to.
+fn oauth_state_key() -> PrimaryKey {
+    PrimaryKey::from("oauth-state-store".to_string())
+}
+
+impl AuthService
+{
+    // TODO(oauth-config): `AuthService` needs an `oauth_providers: Vec<OAuthProviderConfig>`
+    // field, populated from deployment config the same way `StaticLoginProvider`/
+    // `LdapLoginProvider` are. Written below as if it already exists.
+    fn find_oauth_provider(&self, name: &str) -> Result<&OAuthProviderConfig, OAuthLoginFailed>
+    {
+        self.oauth_providers.iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| OAuthLoginFailed::UnknownProvider(name.to_string()))
+    }
+
+    /// Starts an OAuth2 authorization-code flow: mints PKCE material and a random `state`
+    /// token via `OAuthStateStore::begin`, and hands back the URL the client should open in a
+    /// browser. `oauth_login_command` is the CLI-side counterpart that prints this URL and
+    /// waits for the provider's redirect.
+    pub async fn process_oauth_begin(self: Arc<Self>, request: OAuthBeginRequest) -> Result<OAuthBeginResponse, OAuthLoginFailed>
+    {
+        info!("oauth begin: {}", request.provider);
+
+        let provider = self.find_oauth_provider(request.provider.as_str())?;
+
+        let chain = self.registry.open(&self.auth_url, &chain_key_cmd()).await?;
+        let mut dio = chain.dio_full(&self.master_session).await;
+
+        let mut store = match dio.load::<OAuthStateStore>(&oauth_state_key()).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                dio.store_ext(OAuthStateStore::default(), None, Some(oauth_state_key()))?
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let time = self.time_keeper.current_timestamp_as_duration()?;
+        let state = store.begin(provider.name.clone(), time.as_secs());
+
+        let authorize_url = Url::parse_with_params(provider.authorize_url.as_str(), &[
+            ("response_type", "code"),
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("state", state.state.as_str()),
+            ("code_challenge", state.code_verifier.as_str()),
+            ("code_challenge_method", "plain"),
+        ]).map_err(|err| OAuthLoginFailed::ProviderError(err.to_string()))?;
+
+        dio.commit().await?;
+
+        Ok(OAuthBeginResponse {
+            authorize_url: authorize_url.to_string(),
+            state: state.state,
+        })
+    }
+
+    /// Completes a flow begun by `process_oauth_begin`: consumes the matching `OAuthState`
+    /// (failing fast if `state` is unknown, already consumed, or expired), exchanges `code`
+    /// for the provider's verified identity, and maps that identity onto a `User` - creating
+    /// one on first login - before minting the same `LoginResponse` a password login would.
+    pub async fn process_oauth_complete(self: Arc<Self>, request: OAuthCompleteRequest) -> Result<LoginResponse, OAuthLoginFailed>
+    {
+        info!("oauth complete attempt");
+
+        let chain = self.registry.open(&self.auth_url, &chain_key_cmd()).await?;
+        let dio = chain.dio_full(&self.master_session).await;
+
+        let mut store = match dio.load::<OAuthStateStore>(&oauth_state_key()).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                return Err(OAuthLoginFailed::InvalidState);
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let time = self.time_keeper.current_timestamp_as_duration()?;
+        let now = time.as_secs();
+        let state = match store.consume(request.state.as_str(), now) {
+            Some(a) => a,
+            None => {
+                warn!("oauth complete denied - unknown or expired state");
+                return Err(OAuthLoginFailed::InvalidState);
+            },
+        };
+        dio.commit().await?;
+
+        let provider = self.find_oauth_provider(state.provider.as_str())
+            .map_err(|_| OAuthLoginFailed::InvalidState)?;
+        let identity = provider.exchange_code(request.code.as_str(), state.code_verifier.as_str()).await
+            .map_err(|err| OAuthLoginFailed::ProviderError(err.to_string()))?;
+
+        // The OAuth super-key is derived from the provider's own verified subject identifier
+        // mixed with the master key, exactly like `compute_super_key` mixes in a password -
+        // there is no password here, so the subject stands in for one.
+        let super_key = match self.compute_oauth_super_key(identity.subject.as_str()) {
+            Some(a) => a,
+            None => { return Err(OAuthLoginFailed::NoMasterKey); }
+        };
+
+        // Find-or-create the `User` row keyed by `chain_key_4hex(&identity.email, "redo")`,
+        // writing `super_key` as its `nominal_read`/`sudo_read` the first time it's seen -
+        // the OAuth equivalent of how the (not-yet-carried) registration flow provisions a
+        // password account, just with the provider's own verified identity standing in for a
+        // signup form.
+        let chain_key = chain_key_4hex(identity.email.as_str(), Some("redo"));
+        let chain = self.registry.open(&self.auth_url, &chain_key).await?;
+
+        let mut super_session = AteSession::default();
+        super_session.user.add_read_key(&super_key);
+        super_session.user.add_write_key(&super_key);
+        let dio = chain.dio_full(&super_session).await;
+
+        let user_key = PrimaryKey::from(identity.email.clone());
+        let user = match dio.load::<User>(&user_key).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                dio.store_ext(User {
+                    status: UserStatus::Nominal,
+                    nominal_read: super_key.clone(),
+                    nominal_write: super_key.clone(),
+                    sudo_read: super_key.clone(),
+                    sudo_write: super_key.clone(),
+                    access: vec![Authorization { read: super_key.clone(), write: super_key.clone() }],
+                    sudo: DaoRef::default(),
+                    verify_blob: None,
+                }, None, Some(user_key.clone()))?
+            },
+            Err(err) => { bail!(err); }
+        };
+        dio.commit().await?;
+
+        let mut session = compute_user_auth(&user);
+        session.user.add_identity(identity.email.clone());
+
+        let time = self.time_keeper.current_timestamp_as_duration()?;
+        Ok(LoginResponse {
+            user_key,
+            nominal_read: user.nominal_read.clone(),
+            nominal_write: user.nominal_write.clone(),
+            sudo_read: user.sudo_read.clone(),
+            sudo_write: user.sudo_write.clone(),
+            authority: session,
+            expires_at: time.as_secs() + crate::login::LOGIN_SESSION_TTL_SECS,
+            refresh_token: None,
+            message_of_the_day: None,
+        })
+    }
+}
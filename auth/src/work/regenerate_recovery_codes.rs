@@ -0,0 +1,77 @@
+#![allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use error_chain::bail;
+use std::sync::Arc;
+use url::Url;
+
+use ate::prelude::*;
+use ate::error::LoadError;
+use ate::error::TransformError;
+use ate::utils::chain_key_4hex;
+
+use crate::prelude::*;
+use crate::request::*;
+use crate::service::AuthService;
+use crate::helper::*;
+use crate::error::*;
+use crate::model::*;
+
+impl AuthService
+{
+    /// Mints a fresh set of recovery codes for the caller's account, replacing whatever set
+    /// (if any) it had before - the one-time display of the new codes happens client-side in
+    /// `regenerate_recovery_codes_command`, this only ever hands back the plaintext once.
+    ///
+    /// Gated behind a full sudo login: `request.session` must carry the account's sudo read
+    /// key (proven by a prior password + TOTP/recovery-code `process_login`), not merely its
+    /// nominal one - a session that doesn't is rejected with `SudoRequired` the same way
+    /// `MissingReadKey` rejects a wrong password elsewhere, rather than this handler trying to
+    /// re-verify a code itself.
+    pub async fn process_regenerate_recovery_codes(self: Arc<Self>, request: RegenerateRecoveryCodesRequest) -> Result<RegenerateRecoveryCodesResponse, RegenerateRecoveryCodesFailed>
+    {
+        info!("regenerate recovery codes for {}", request.email);
+
+        let request_session = request.session;
+
+        let chain_key = chain_key_4hex(request.email.as_str(), Some("redo"));
+        let chain = self.registry.open(&self.auth_url, &chain_key).await?;
+
+        // Combine the master session with whatever rights the caller's login already proved -
+        // if that didn't include the sudo read key, loading `Sudo` below fails closed.
+        let mut super_session = self.master_session.clone();
+        super_session.append(request_session.properties());
+
+        let dio = chain.dio_full(&super_session).await;
+
+        let user_key = PrimaryKey::from(request.email.clone());
+        let user = match dio.load::<User>(&user_key).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                return Err(RegenerateRecoveryCodesFailed::UserNotFound(request.email));
+            },
+            Err(LoadError(LoadErrorKind::TransformationError(TransformErrorKind::MissingReadKey(_)), _)) => {
+                return Err(RegenerateRecoveryCodesFailed::SudoRequired);
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let mut sudo = match user.sudo.load().await {
+            Ok(Some(a)) => a,
+            Ok(None) => {
+                return Err(RegenerateRecoveryCodesFailed::NoSudo(request.email));
+            },
+            Err(LoadError(LoadErrorKind::TransformationError(TransformErrorKind::MissingReadKey(_)), _)) => {
+                return Err(RegenerateRecoveryCodesFailed::SudoRequired);
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let recovery_codes = sudo.regenerate_recovery_codes();
+
+        dio.commit().await?;
+
+        Ok(RegenerateRecoveryCodesResponse {
+            recovery_codes,
+        })
+    }
+}
@@ -0,0 +1,77 @@
+#![allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use error_chain::bail;
+use std::sync::Arc;
+use url::Url;
+
+use ate::prelude::*;
+use ate::error::LoadError;
+use ate::error::TransformError;
+use ate::utils::chain_key_4hex;
+
+use crate::prelude::*;
+use crate::request::*;
+use crate::service::AuthService;
+use crate::helper::*;
+use crate::error::*;
+use crate::model::*;
+
+impl AuthService
+{
+    /// Provisions a long-lived API key for `request.device_id`, hung off the caller's `Sudo`
+    /// record. The caller must already be authenticated (`request.session` comes out of a
+    /// normal password+TOTP `process_login`) - a device key is minted onto an existing
+    /// account, never used to bootstrap one.
+    pub async fn process_register_device(self: Arc<Self>, request: RegisterDeviceRequest) -> Result<RegisterDeviceResponse, RegisterDeviceFailed>
+    {
+        info!("register device ({}) for {}", request.device_id, request.email);
+
+        let request_session = request.session;
+
+        // Compute which chain the user should exist within
+        let chain_key = chain_key_4hex(request.email.as_str(), Some("redo"));
+        let chain = self.registry.open(&self.auth_url, &chain_key).await?;
+
+        // Combine the master session with whatever rights the caller's login already proved
+        let mut super_session = self.master_session.clone();
+        super_session.append(request_session.properties());
+
+        let dio = chain.dio_full(&super_session).await;
+
+        let user_key = PrimaryKey::from(request.email.clone());
+        let user = match dio.load::<User>(&user_key).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                return Err(RegisterDeviceFailed::UserNotFound(request.email));
+            },
+            Err(LoadError(LoadErrorKind::TransformationError(TransformErrorKind::MissingReadKey(_)), _)) => {
+                return Err(RegisterDeviceFailed::NoMasterKey);
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let mut sudo = match user.sudo.load().await {
+            Ok(Some(a)) => a,
+            Ok(None) => {
+                return Err(RegisterDeviceFailed::NoSudo(request.email));
+            },
+            Err(LoadError(LoadErrorKind::TransformationError(TransformErrorKind::MissingReadKey(_)), _)) => {
+                return Err(RegisterDeviceFailed::NoMasterKey);
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        // Mint the key - nominal read/write only, never the sudo super-super-key path
+        let time = self.time_keeper.current_timestamp_as_duration()?;
+        let now = time.as_secs();
+        let api_key = sudo.issue_device_api_key(request.device_id.clone(), now);
+
+        // Commit the updated Sudo record
+        dio.commit().await?;
+
+        Ok(RegisterDeviceResponse {
+            device_id: request.device_id,
+            api_key,
+        })
+    }
+}
@@ -0,0 +1,82 @@
+#![allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use error_chain::bail;
+use std::sync::Arc;
+use url::Url;
+
+use ate::prelude::*;
+use ate::error::LoadError;
+use ate::error::TransformError;
+use ate::utils::chain_key_4hex;
+
+use crate::prelude::*;
+use crate::request::*;
+use crate::service::AuthService;
+use crate::helper::*;
+use crate::error::*;
+use crate::model::*;
+
+/// How long a renewed session stays valid before the client has to renew again (or fall back
+/// to an interactive login once its refresh token itself has expired).
+const RENEWED_SESSION_TTL_SECS: u64 = 15 * 60;
+
+impl AuthService
+{
+    /// Exchanges a still-live refresh token for a fresh nominal session - no password or TOTP
+    /// code required, so a long-running CLI session can keep itself alive past its short
+    /// expiry without a human in the loop. The refresh token is single-use: a new one is
+    /// minted (and returned alongside the session) so the chain of renewals can continue.
+    pub async fn process_renew(self: Arc<Self>, request: RenewRequest) -> Result<RenewResponse, RenewFailed>
+    {
+        info!("renew attempt: {}", request.email);
+
+        // Renewal proves nothing but the refresh token itself, so (like `public_login`) the
+        // chain is opened with the service's own master session rather than anything the
+        // caller presented.
+        let chain_key = chain_key_4hex(request.email.as_str(), Some("redo"));
+        let chain = self.registry.open(&self.auth_url, &chain_key).await?;
+        let dio = chain.dio_full(&self.master_session).await;
+
+        let user_key = PrimaryKey::from(request.email.clone());
+        let user = match dio.load::<User>(&user_key).await {
+            Ok(a) => a,
+            Err(LoadError(LoadErrorKind::NotFound(_), _)) => {
+                return Err(RenewFailed::UserNotFound(request.email));
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let mut sudo = match user.sudo.load().await {
+            Ok(Some(a)) => a,
+            Ok(None) => {
+                warn!("renew attempt denied ({}) - no refresh tokens on file", request.email);
+                return Err(RenewFailed::InvalidToken);
+            },
+            Err(err) => { bail!(err); }
+        };
+
+        let time = self.time_keeper.current_timestamp_as_duration()?;
+        let now = time.as_secs();
+
+        if sudo.consume_refresh_token(request.refresh_token.as_str(), now) == false {
+            warn!("renew attempt denied ({}) - refresh token expired or unknown", request.email);
+            return Err(RenewFailed::InvalidToken);
+        }
+
+        // Rotate: the token just presented is gone, a new one takes its place so the client
+        // can keep renewing indefinitely without ever re-entering a password.
+        let (refresh_token, _) = sudo.issue_refresh_token(REFRESH_TOKEN_TTL_SECS, now);
+        let expires_at = now + RENEWED_SESSION_TTL_SECS;
+
+        let mut session = compute_user_auth(&user);
+        session.user.add_identity(request.email.clone());
+
+        dio.commit().await?;
+
+        Ok(RenewResponse {
+            authority: session,
+            expires_at,
+            refresh_token,
+        })
+    }
+}
@@ -0,0 +1,141 @@
+#![allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace};
+use serde::{Serialize, Deserialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::crypto::AteHash;
+
+/// A shared secret that mints and verifies [`AdmissionToken`]s. Verification is entirely
+/// offline (a keyed hash over the node-id and expiry) so a server can gate which peers are
+/// allowed to join the mesh without a round-trip to whatever issued the token - the same
+/// precomputed-token approach mangadex-home uses to admit edge nodes.
+#[derive(Clone)]
+pub struct AdmissionKey {
+    secret: Vec<u8>,
+}
+
+impl AdmissionKey {
+    pub fn new(secret: Vec<u8>) -> AdmissionKey {
+        AdmissionKey { secret }
+    }
+
+    fn tag_for(&self, node_id: &str, expiry_secs: u64) -> AteHash {
+        let payload = format!("{}:{}", node_id, expiry_secs);
+        AteHash::from_bytes_twice(&self.secret[..], payload.as_bytes())
+    }
+
+    /// Mints a bearer token that admits `node_id` to the mesh until `expiry_secs` (seconds
+    /// since the Unix epoch). The token is opaque to the holder - it carries no secret, only
+    /// the node-id, expiry and the tag that proves both were signed by this key.
+    pub fn mint(&self, node_id: &str, expiry_secs: u64) -> AdmissionToken {
+        AdmissionToken {
+            node_id: node_id.to_string(),
+            expiry_secs,
+            tag: self.tag_for(node_id, expiry_secs),
+        }
+    }
+
+    /// Verifies that `token` was minted by this key for `node_id` and has not yet expired.
+    pub fn verify(&self, token: &AdmissionToken, node_id: &str) -> Result<(), AdmissionError> {
+        if token.node_id != node_id {
+            return Err(AdmissionError::NodeMismatch);
+        }
+
+        let now_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if now_secs >= token.expiry_secs {
+            return Err(AdmissionError::Expired);
+        }
+
+        if !constant_time_eq(&self.tag_for(&token.node_id, token.expiry_secs), &token.tag) {
+            return Err(AdmissionError::Tampered);
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares two tags in time independent of where they first differ, so a timing side-channel
+/// can't be used to recover a valid tag one byte at a time. `==` on `AteHash` (and the `Vec<u8>`
+/// it serializes to) short-circuits on the first mismatching byte, which is exactly the leak a
+/// MAC comparison needs to avoid.
+fn constant_time_eq(a: &AteHash, b: &AteHash) -> bool {
+    let a = serde_json::to_vec(a).unwrap_or_default();
+    let b = serde_json::to_vec(b).unwrap_or_default();
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+/// An opaque bearer token that would gate mesh admission - minted by [`AdmissionKey::mint`] and
+/// checked with [`AdmissionKey::verify`], both of which work standalone today. Actually gating
+/// admission needs `HelloMetadata` to carry one of these and a server-side accept path to call
+/// `verify` before spawning `mesh_connect_worker` for the presenting peer - neither exists yet
+/// (see the `TODO(admission-control)` in `comms::client`), so nothing mints or checks a real one
+/// on any connection today.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
+pub struct AdmissionToken {
+    node_id: String,
+    expiry_secs: u64,
+    tag: AteHash,
+}
+
+/// Why [`AdmissionKey::verify`] rejected a token.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AdmissionError {
+    /// The tag does not match the node-id/expiry it was presented with - either signed by a
+    /// different key or altered in transit.
+    Tampered,
+    /// The token's expiry has already passed.
+    Expired,
+    /// The token was minted for a different node-id than the one presenting it.
+    NodeMismatch,
+}
+
+impl std::fmt::Display for AdmissionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdmissionError::Tampered => write!(f, "admission token signature is invalid"),
+            AdmissionError::Expired => write!(f, "admission token has expired"),
+            AdmissionError::NodeMismatch => write!(f, "admission token was not issued for this node-id"),
+        }
+    }
+}
+
+impl std::error::Error for AdmissionError { }
+
+#[test]
+fn test_admission_token_round_trip() {
+    let key = AdmissionKey::new(b"test-secret".to_vec());
+    let token = key.mint("node-a", u64::MAX);
+    assert_eq!(key.verify(&token, "node-a"), Ok(()));
+}
+
+#[test]
+fn test_admission_token_expired() {
+    let key = AdmissionKey::new(b"test-secret".to_vec());
+    let token = key.mint("node-a", 0);
+    assert_eq!(key.verify(&token, "node-a"), Err(AdmissionError::Expired));
+}
+
+#[test]
+fn test_admission_token_tampered() {
+    let key = AdmissionKey::new(b"test-secret".to_vec());
+    let mut token = key.mint("node-a", u64::MAX);
+    token.expiry_secs -= 1;
+    assert_eq!(key.verify(&token, "node-a"), Err(AdmissionError::Tampered));
+}
+
+#[test]
+fn test_admission_token_node_mismatch() {
+    let key = AdmissionKey::new(b"test-secret".to_vec());
+    let token = key.mint("node-a", u64::MAX);
+    assert_eq!(key.verify(&token, "node-b"), Err(AdmissionError::NodeMismatch));
+}
@@ -0,0 +1,75 @@
+#![allow(unused_imports)]
+use serde::{Serialize, Deserialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use rand::RngCore;
+
+use crate::crypto::KeySize;
+
+/// Which AEAD cipher protects frames once the key exchange has agreed on a key. Not yet wired
+/// into an actual handshake: doing so means advertising this in `HelloMetadata` and having both
+/// `key_exchange::mesh_key_exchange_sender` and its receiving-side counterpart settle on
+/// `negotiate`'s result before the first encrypted frame goes out, which needs the `hello`/
+/// `key_exchange`/`server` modules this tree does not currently carry - see the
+/// `TODO(cipher-suite)` in `comms::client`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherSuite {
+    /// AES-256-GCM - the default, and the only suite earlier mesh versions understand.
+    Aes256Gcm,
+    /// ChaCha20-Poly1305 at the same 256-bit security level - faster than AES-GCM on nodes
+    /// without AES-NI (e.g. many ARM boards).
+    ChaCha20Poly1305,
+}
+
+impl CipherSuite {
+    /// The `KeySize` this suite derives its key at - both suites use a 256-bit key, so picking
+    /// a suite never changes how big a secret the key exchange needs to agree on.
+    pub fn key_size(&self) -> KeySize {
+        KeySize::Bit256
+    }
+
+    /// Picks the best suite both `local_supported` and `peer_supported` list, preferring
+    /// `local_supported`'s order. Falls back to `Aes256Gcm` - understood by every version of
+    /// this crate - if the two sides share nothing else.
+    pub fn negotiate(local_supported: &[CipherSuite], peer_supported: &[CipherSuite]) -> CipherSuite {
+        local_supported
+            .iter()
+            .find(|candidate| peer_supported.contains(candidate))
+            .copied()
+            .unwrap_or(CipherSuite::Aes256Gcm)
+    }
+}
+
+impl Default for CipherSuite {
+    fn default() -> CipherSuite {
+        CipherSuite::Aes256Gcm
+    }
+}
+
+/// A per-connection, monotonically increasing nonce generator for `ChaCha20Poly1305` frames.
+/// Each `new()` rolls a fresh random salt, so a sequence constructed per completed handshake
+/// never replays a nonce a previous connection's sequence used, even if the negotiated key were
+/// reused across reconnects. Nothing in this tree constructs one (see `CipherSuite`).
+pub struct FrameNonceSequence {
+    salt: [u8; 4],
+    counter: AtomicU64,
+}
+
+impl FrameNonceSequence {
+    /// Starts a new sequence with a fresh random salt.
+    pub fn new() -> FrameNonceSequence {
+        let mut salt = [0u8; 4];
+        rand::thread_rng().fill_bytes(&mut salt);
+        FrameNonceSequence { salt, counter: AtomicU64::new(0) }
+    }
+
+    /// Returns the next 12-byte ChaCha20-Poly1305 nonce: the salt rolled at construction,
+    /// followed by an 8-byte big-endian monotonic counter. Never repeats within the lifetime of
+    /// one `FrameNonceSequence`, short of sending more than 2^64 frames on a single connection.
+    pub fn next(&self) -> [u8; 12] {
+        let counter = self.counter.fetch_add(1, Ordering::SeqCst);
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(&self.salt);
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+}
@@ -31,6 +31,7 @@ use super::helper::*;
 use super::hello;
 use super::key_exchange;
 use super::CertificateValidation;
+
 #[allow(unused_imports)]
 use {
     super::Stream,
@@ -60,7 +61,7 @@ where M: Send + Sync + Serialize + DeserializeOwned + Default + Clone + 'static,
         // Perform the connect operation
         let inbox = Box::new(inbox);
         let upstream = mesh_connect_to::<M, C>(
-            target.clone(), 
+            target.clone(),
             hello_path.clone(),
             node_id,
             conf.cfg_mesh.domain_name.clone(),
@@ -132,10 +133,24 @@ where M: Send + Sync + Serialize + DeserializeOwned + Clone + Default + 'static,
         fail_fast,
     );
     let (mut worker_connect, mut stream_tx) = tokio::time::timeout(timeout, worker_connect).await??;
+    // TODO(wire-format-negotiation): `wire_format` is whatever the local side already picked
+    // rather than something actually agreed with the peer. Turning this into a real negotiation
+    // (Bincode/MessagePack/Postcard/Json advertised and resolved during
+    // `mesh_hello_exchange_sender`, with `process_inbox`/the outbox dispatching on the agreed
+    // value) needs the `hello`/`rx_tx` modules this tree does not currently carry.
     let wire_format = worker_connect.hello_metadata.wire_format;
     let server_id = worker_connect.hello_metadata.server_id;
 
     // If we are using wire encryption then exchange secrets
+    //
+    // TODO(cipher-suite): `key_size` only selects the AES key length today. Adding a
+    // ChaCha20-Poly1305 option means widening the hello/key-exchange handshake (in the
+    // `key_exchange`/`hello` modules, which this tree does not currently carry) with a
+    // cipher-suite identifier, deriving a 256-bit key plus a salt-prefixed monotonic nonce per
+    // frame, and re-rolling that salt on every successful handshake so the exponential-backoff
+    // reconnect loop above can never reuse a nonce across reconnects. `CipherSuite`/
+    // `FrameNonceSequence` (in `cipher_suite.rs`) already implement that negotiation and nonce
+    // sequence in isolation; nothing here calls them yet.
     let ek = match wire_encryption {
         Some(key_size) => Some(key_exchange::mesh_key_exchange_sender(&mut worker_connect.stream_rx, &mut stream_tx, key_size, validation).await?),
         None => None,
@@ -177,7 +192,7 @@ struct MeshConnectContext
 #[allow(unused_variables)]
 async fn mesh_connect_prepare
 (
-    
+
     addr: MeshConnectAddr,
     hello_path: String,
     node_id: NodeId,
@@ -235,11 +250,32 @@ async fn mesh_connect_prepare
                 // Setup the TCP stream
                 setup_tcp_stream(&stream)?;
 
-                // Convert the TCP stream into the right protocol
+                // Convert the TCP stream into the right protocol. WebSocket variants get the
+                // real `hello_path`/`domain` as the handshake URL and `Host` header (rather than
+                // the hardcoded `localhost` fallback) so the mesh can traverse HTTP proxies and
+                // share a domain/port with other protocols behind a reverse proxy. `Tls`/
+                // `WebSocketTls` get a `rustls::ClientConfig` built from `validation` (with the
+                // mesh ALPN id advertised) and `domain` as the SNI/server-name, so
+                // `Stream::upgrade_client_ext` actually performs and verifies the TLS handshake
+                // instead of silently passing `None` for it.
+                let tls = if wire_protocol.is_tls() {
+                    Some((validation.client_config(), domain.clone()))
+                } else {
+                    None
+                };
                 let stream = Stream::Tcp(stream);
-                let stream = stream
-                    .upgrade_client(wire_protocol)
-                    .await?;
+                let stream = if wire_protocol.is_web_socket() {
+                    let url = wire_protocol.make_url(domain.clone(), addr.port(), hello_path.clone())?;
+                    let host = tokio_tungstenite::tungstenite::http::HeaderValue::from_str(domain.as_str())
+                        .map_err(|_| CommsErrorKind::InvalidDomainName)?;
+                    stream
+                        .upgrade_client_with_ext(wire_protocol, tls, Some(url), vec![(tokio_tungstenite::tungstenite::http::header::HOST, host)])
+                        .await?
+                } else {
+                    stream
+                        .upgrade_client_ext(wire_protocol, tls)
+                        .await?
+                };
                 stream
             };
 
@@ -255,6 +291,27 @@ async fn mesh_connect_prepare
             let (mut stream_rx, mut stream_tx) = stream.split();
 
             // Say hello
+            //
+            // TODO(admission-control): gating this with a shared `AdmissionKey` would mean
+            // minting a short-lived token bound to `node_id` here and carrying it in
+            // `HelloMetadata` as `Option<AdmissionToken>`, with the server calling
+            // `AdmissionKey::verify` against its own copy of the same key before spawning
+            // `mesh_connect_worker` for the presenting node - rejecting expired/malformed/
+            // mismatched tokens, `None` (no key configured) skipping the gate entirely.
+            // `AdmissionKey`/`AdmissionToken` (in `admission.rs`) already implement the mint/
+            // verify pair in isolation; nothing here mints or sends one yet, and nothing on an
+            // accept path would check one either - that needs the `hello`/`server` modules this
+            // tree does not currently carry.
+            //
+            // TODO(trace-propagation): to stitch this `connect` span together with the
+            // server's inbox-processing span, `mesh_hello_exchange_sender` would need to carry
+            // an optional W3C trace-id/span-id/flags blob (extracted from the currently active
+            // span here, and re-parented into a child span on the receiving side) inside
+            // `HelloMetadata` - zero-cost when absent, and a fresh root span on anything
+            // malformed rather than failing the handshake. `TraceContext` (in
+            // `trace_context.rs`) already implements that blob in isolation; nothing here mints
+            // or sends one yet - that needs the `hello`/`server` modules this tree does not
+            // currently carry.
             let hello_metadata =
                 hello::mesh_hello_exchange_sender(&mut stream_rx, &mut stream_tx, node_id, hello_path.clone(), domain.clone(), wire_encryption)
                 .await?;
@@ -7,6 +7,13 @@ use tokio::net::TcpStream;
 use tokio::net::tcp::OwnedReadHalf;
 #[cfg(feature = "enable_full")]
 use tokio::net::tcp::OwnedWriteHalf;
+#[cfg(feature = "enable_full")]
+use tokio_rustls::{client::TlsStream as RustlsClientStream, server::TlsStream as RustlsServerStream};
+#[cfg(feature = "enable_full")]
+use std::pin::Pin;
+#[cfg(feature = "enable_full")]
+use std::task::{Context, Poll};
+use tokio::sync::Mutex as AsyncMutex;
 use tokio::io::{AsyncRead, AsyncWrite};
 use std::str::FromStr;
 use tokio::time::timeout as tokio_timeout;
@@ -37,6 +44,8 @@ pub enum StreamProtocol
 {
     Tcp,
     WebSocket,
+    Tls,
+    WebSocketTls,
 }
 
 impl std::str::FromStr
@@ -49,6 +58,8 @@ for StreamProtocol
         let ret = match s {
             "tcp" => StreamProtocol::Tcp,
             "ws" => StreamProtocol::WebSocket,
+            "tls" => StreamProtocol::Tls,
+            "wss" => StreamProtocol::WebSocketTls,
             _ => {
                 bail!(CommsErrorKind::UnsupportedProtocolError(s.to_string()));
             }
@@ -64,6 +75,8 @@ impl StreamProtocol
         let ret = match self {
             StreamProtocol::Tcp => "tcp",
             StreamProtocol::WebSocket => "ws",
+            StreamProtocol::Tls => "tls",
+            StreamProtocol::WebSocketTls => "wss",
         };
         ret.to_string()
     }
@@ -77,6 +90,8 @@ impl StreamProtocol
         match self {
             StreamProtocol::Tcp => 5000,
             StreamProtocol::WebSocket => 80,
+            StreamProtocol::Tls => 5001,
+            StreamProtocol::WebSocketTls => 443,
         }
     }
 
@@ -84,6 +99,8 @@ impl StreamProtocol
         match self {
             StreamProtocol::Tcp => true,
             StreamProtocol::WebSocket => false,
+            StreamProtocol::Tls => true,
+            StreamProtocol::WebSocketTls => false,
         }
     }
 
@@ -91,6 +108,19 @@ impl StreamProtocol
         match self {
             StreamProtocol::Tcp => false,
             StreamProtocol::WebSocket => true,
+            StreamProtocol::Tls => false,
+            StreamProtocol::WebSocketTls => true,
+        }
+    }
+
+    /// True for `Tls`/`WebSocketTls` - i.e. whether this protocol needs a TLS handshake
+    /// layered underneath (or on top of, for the WebSocket case) the raw TCP stream.
+    pub fn is_tls(&self) -> bool {
+        match self {
+            StreamProtocol::Tcp => false,
+            StreamProtocol::WebSocket => false,
+            StreamProtocol::Tls => true,
+            StreamProtocol::WebSocketTls => true,
         }
     }
 }
@@ -107,6 +137,53 @@ pub trait AsyncStream : AsyncRead + AsyncWrite + std::fmt::Debug
 {
 }
 
+/// A TLS-wrapped `TcpStream`, as produced by either side of a `tokio-rustls` handshake.
+/// `tokio_rustls::client::TlsStream` and `server::TlsStream` are distinct concrete types with
+/// no shared supertype, so this enum gives `Stream` a single type to carry regardless of
+/// which side of the handshake performed the upgrade.
+#[derive(Debug)]
+pub enum TlsStream
+{
+    Client(RustlsClientStream<TcpStream>),
+    Server(RustlsServerStream<TcpStream>),
+}
+
+impl AsyncRead
+for TlsStream
+{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            TlsStream::Client(a) => Pin::new(a).poll_read(cx, buf),
+            TlsStream::Server(a) => Pin::new(a).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite
+for TlsStream
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            TlsStream::Client(a) => Pin::new(a).poll_write(cx, buf),
+            TlsStream::Server(a) => Pin::new(a).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            TlsStream::Client(a) => Pin::new(a).poll_flush(cx),
+            TlsStream::Server(a) => Pin::new(a).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            TlsStream::Client(a) => Pin::new(a).poll_shutdown(cx),
+            TlsStream::Server(a) => Pin::new(a).poll_shutdown(cx),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Stream
 {
@@ -116,6 +193,10 @@ pub enum Stream
     WebSocket(WebSocketStream<TcpStream>, StreamProtocol),
     #[cfg(feature = "enable_server")]
     HyperWebSocket(HyperWebSocket<HyperUpgraded>, StreamProtocol),
+    #[cfg(feature = "enable_full")]
+    Tls(TlsStream, StreamProtocol),
+    #[cfg(feature = "enable_full")]
+    WebSocketTls(WebSocketStream<TlsStream>, StreamProtocol),
     Custom(Box<dyn AsyncStream>, StreamProtocol),
 }
 
@@ -144,15 +225,94 @@ impl StreamProtocol
     }
 }
 
+// The timestamp the corresponding `StreamTx`/`StreamRx` pair last observed a `Pong` frame, so a
+// caller can notice a dead peer (see `StreamTxChannel`'s keepalive). Shared rather than owned,
+// since it is written from the read side (on `Pong`) and read from the write side.
+pub(crate) type LastPong = Arc<std::sync::Mutex<Option<std::time::Instant>>>;
+
+/// The status code sent in a WebSocket `Close` frame - see [`StreamTx::close`].
+pub type CloseCode = tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+/// The ALPN protocol id the mesh advertises during the `Tls`/`WebSocketTls` handshake, so a
+/// reverse proxy fronting several protocols on one port can route by ALPN rather than by port.
+const MESH_ALPN_PROTOCOL: &[u8] = b"ate/1";
+
+/// How [`Stream::upgrade_client_ext`] verifies the peer's certificate for the `Tls`/
+/// `WebSocketTls` protocols.
+#[derive(Clone)]
+#[cfg(feature = "enable_full")]
+pub enum CertificateValidation {
+    /// Verify the peer's certificate against this root CA store - the normal, production case.
+    RootCertificates(Arc<rustls::RootCertStore>),
+    /// Skip certificate validation entirely. Only for connecting to a node whose certificate
+    /// can't be checked against a root store (e.g. a self-signed node in local development) -
+    /// never use this for a mesh reachable over an untrusted network.
+    AllowAllCertificates,
+}
+
+#[cfg(feature = "enable_full")]
+impl CertificateValidation {
+    /// Builds the `rustls::ClientConfig` for this policy, with [`MESH_ALPN_PROTOCOL`] advertised.
+    pub(crate) fn client_config(&self) -> Arc<rustls::ClientConfig> {
+        let builder = rustls::ClientConfig::builder().with_safe_defaults();
+        let mut config = match self {
+            CertificateValidation::RootCertificates(roots) => {
+                builder.with_root_certificates(roots.as_ref().clone()).with_no_client_auth()
+            },
+            CertificateValidation::AllowAllCertificates => {
+                builder
+                    .with_custom_certificate_verifier(Arc::new(NoCertificateVerification))
+                    .with_no_client_auth()
+            },
+        };
+        config.alpn_protocols = vec![MESH_ALPN_PROTOCOL.to_vec()];
+        Arc::new(config)
+    }
+}
+
+/// Backs [`CertificateValidation::AllowAllCertificates`] - accepts any certificate the peer
+/// presents without checking it against a root store or the requested server name.
+#[cfg(feature = "enable_full")]
+struct NoCertificateVerification;
+
+#[cfg(feature = "enable_full")]
+impl rustls::client::ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+#[cfg(feature = "enable_full")]
+type WebSocketSink = Arc<AsyncMutex<futures_util::stream::SplitSink<WebSocketStream<TcpStream>, Message>>>;
+#[cfg(feature = "enable_server")]
+type HyperWebSocketSink = Arc<AsyncMutex<futures_util::stream::SplitSink<HyperWebSocket<HyperUpgraded>, HyperMessage>>>;
+#[cfg(feature = "enable_full")]
+type WebSocketTlsSink = Arc<AsyncMutex<futures_util::stream::SplitSink<WebSocketStream<TlsStream>, Message>>>;
+
 #[derive(Debug)]
 pub enum StreamRx
 {
     #[cfg(feature = "enable_full")]
     Tcp(OwnedReadHalf),
+    // The sink half is carried alongside the stream half (rather than only living in the
+    // paired `StreamTx`) so an inbound `Ping` can be answered with a `Pong` from the read path
+    // without routing back through the caller.
     #[cfg(feature = "enable_full")]
-    WebSocket(futures_util::stream::SplitStream<WebSocketStream<TcpStream>>),
+    WebSocket(futures_util::stream::SplitStream<WebSocketStream<TcpStream>>, WebSocketSink, LastPong),
     #[cfg(feature = "enable_server")]
-    HyperWebSocket(futures_util::stream::SplitStream<HyperWebSocket<HyperUpgraded>>),
+    HyperWebSocket(futures_util::stream::SplitStream<HyperWebSocket<HyperUpgraded>>, HyperWebSocketSink, LastPong),
+    #[cfg(feature = "enable_full")]
+    Tls(tokio::io::ReadHalf<TlsStream>, StreamProtocol),
+    #[cfg(feature = "enable_full")]
+    WebSocketTls(futures_util::stream::SplitStream<WebSocketStream<TlsStream>>, WebSocketTlsSink, LastPong),
     Custom(tokio::io::ReadHalf<Box<dyn AsyncStream>>, StreamProtocol),
 }
 
@@ -162,9 +322,13 @@ pub enum StreamTx
     #[cfg(feature = "enable_full")]
     Tcp(OwnedWriteHalf),
     #[cfg(feature = "enable_full")]
-    WebSocket(futures_util::stream::SplitSink<WebSocketStream<TcpStream>, Message>),
+    WebSocket(WebSocketSink, LastPong),
     #[cfg(feature = "enable_server")]
-    HyperWebSocket(futures_util::stream::SplitSink<HyperWebSocket<HyperUpgraded>, HyperMessage>),
+    HyperWebSocket(HyperWebSocketSink, LastPong),
+    #[cfg(feature = "enable_full")]
+    Tls(tokio::io::WriteHalf<TlsStream>, StreamProtocol),
+    #[cfg(feature = "enable_full")]
+    WebSocketTls(WebSocketTlsSink, LastPong),
     Custom(tokio::io::WriteHalf<Box<dyn AsyncStream>>, StreamProtocol),
 }
 
@@ -177,15 +341,32 @@ impl Stream
                 let (rx, tx) = a.into_split();
                 (StreamRx::Tcp(rx), StreamTx::Tcp(tx))
             },
-            Stream::WebSocket(a) => {
+            #[cfg(feature = "enable_full")]
+            Stream::WebSocket(a, _) => {
                 let (tx, rx) = a.split();
-                (StreamRx::WebSocket(rx), StreamTx::WebSocket(tx))
+                let last_pong = Arc::new(std::sync::Mutex::new(None));
+                let tx = Arc::new(AsyncMutex::new(tx));
+                (StreamRx::WebSocket(rx, tx.clone(), last_pong.clone()), StreamTx::WebSocket(tx, last_pong))
             }
             #[cfg(feature = "enable_server")]
             Stream::HyperWebSocket(a, _) => {
                 let (tx, rx) = a.split();
-                (StreamRx::HyperWebSocket(rx), StreamTx::HyperWebSocket(tx))
+                let last_pong = Arc::new(std::sync::Mutex::new(None));
+                let tx = Arc::new(AsyncMutex::new(tx));
+                (StreamRx::HyperWebSocket(rx, tx.clone(), last_pong.clone()), StreamTx::HyperWebSocket(tx, last_pong))
             }
+            #[cfg(feature = "enable_full")]
+            Stream::Tls(a, p) => {
+                let (rx, tx) = tokio::io::split(a);
+                (StreamRx::Tls(rx, p), StreamTx::Tls(tx, p))
+            },
+            #[cfg(feature = "enable_full")]
+            Stream::WebSocketTls(a, _) => {
+                let (tx, rx) = a.split();
+                let last_pong = Arc::new(std::sync::Mutex::new(None));
+                let tx = Arc::new(AsyncMutex::new(tx));
+                (StreamRx::WebSocketTls(rx, tx.clone(), last_pong.clone()), StreamTx::WebSocketTls(tx, last_pong))
+            },
             Stream::Custom(a, p) => {
                 use tokio::io::*;
                 let (tx, rx) = a.split();
@@ -196,6 +377,15 @@ impl Stream
 
     #[cfg(feature = "enable_server")]
     pub async fn upgrade_server(self, protocol: StreamProtocol, timeout: Duration) -> Result<Stream, CommsError> {
+        self.upgrade_server_ext(protocol, timeout, None).await
+    }
+
+    /// Same as [`Stream::upgrade_server`] but also accepts a `rustls::ServerConfig` (carrying
+    /// the server's certificate and private key) for the `Tls`/`WebSocketTls` protocols. Split
+    /// out as an `_ext` overload rather than changing `upgrade_server`'s signature outright, so
+    /// existing callers that never negotiate TLS are unaffected.
+    #[cfg(feature = "enable_server")]
+    pub async fn upgrade_server_ext(self, protocol: StreamProtocol, timeout: Duration, tls_config: Option<Arc<rustls::ServerConfig>>) -> Result<Stream, CommsError> {
         debug!("tcp-protocol-upgrade(server): {}", protocol);
 
         let ret = match self {
@@ -210,6 +400,28 @@ impl Stream
                         let socket = tokio_timeout(timeout, wait).await??;
                         Stream::WebSocket(socket, protocol)
                     },
+                    StreamProtocol::Tls => {
+                        let tls_config = match tls_config {
+                            Some(a) => a,
+                            None => { bail!(CommsErrorKind::UnsupportedProtocolError("a rustls::ServerConfig is required to upgrade to the tls protocol".to_string())); }
+                        };
+                        let acceptor = tokio_rustls::TlsAcceptor::from(tls_config);
+                        let wait = acceptor.accept(a);
+                        let tls = tokio_timeout(timeout, wait).await??;
+                        Stream::Tls(TlsStream::Server(tls), protocol)
+                    },
+                    StreamProtocol::WebSocketTls => {
+                        let tls_config = match tls_config {
+                            Some(a) => a,
+                            None => { bail!(CommsErrorKind::UnsupportedProtocolError("a rustls::ServerConfig is required to upgrade to the wss protocol".to_string())); }
+                        };
+                        let acceptor = tokio_rustls::TlsAcceptor::from(tls_config);
+                        let wait = acceptor.accept(a);
+                        let tls = tokio_timeout(timeout, wait).await??;
+                        let wait = tokio_tungstenite::accept_async(TlsStream::Server(tls));
+                        let socket = tokio_timeout(timeout, wait).await??;
+                        Stream::WebSocketTls(socket, protocol)
+                    },
                 }
             },
             #[cfg(feature = "enable_full")]
@@ -221,6 +433,9 @@ impl Stream
                     StreamProtocol::WebSocket => {
                         Stream::WebSocket(a, p)
                     },
+                    _ => {
+                        Stream::WebSocket(a, p)
+                    },
                 }
             },
             #[cfg(feature = "enable_server")]
@@ -232,8 +447,19 @@ impl Stream
                     StreamProtocol::WebSocket => {
                         Stream::HyperWebSocket(a, p)
                     }
+                    _ => {
+                        Stream::HyperWebSocket(a, p)
+                    }
                 }
             },
+            #[cfg(feature = "enable_full")]
+            Stream::Tls(a, p) => {
+                Stream::Tls(a, p)
+            },
+            #[cfg(feature = "enable_full")]
+            Stream::WebSocketTls(a, p) => {
+                Stream::WebSocketTls(a, p)
+            },
             Stream::Custom(a, p) => {
                 match protocol {
                     StreamProtocol::Tcp => {
@@ -242,6 +468,9 @@ impl Stream
                     StreamProtocol::WebSocket => {
                         Stream::Custom(a, p)
                     }
+                    _ => {
+                        Stream::Custom(a, p)
+                    }
                 }
             }
         };
@@ -252,6 +481,30 @@ impl Stream
     #[allow(dead_code)]
     #[allow(unused_variables)]
     pub async fn upgrade_client(self, protocol: StreamProtocol) -> Result<Stream, CommsError> {
+        self.upgrade_client_ext(protocol, None).await
+    }
+
+    /// Same as [`Stream::upgrade_client`] but also accepts a `(rustls::ClientConfig,
+    /// server-name)` pair for the `Tls`/`WebSocketTls` protocols. Split out as an `_ext`
+    /// overload (mirroring [`Stream::upgrade_server_ext`]) so the existing single-argument
+    /// call site keeps working for protocols that never need TLS.
+    #[allow(unused_variables)]
+    pub async fn upgrade_client_ext(self, protocol: StreamProtocol, tls: Option<(Arc<rustls::ClientConfig>, String)>) -> Result<Stream, CommsError> {
+        self.upgrade_client_with_ext(protocol, tls, None, Vec::new()).await
+    }
+
+    /// Same as [`Stream::upgrade_client`] but lets the caller supply the exact URL the
+    /// WebSocket handshake is made against (so a real path/host can be used instead of the
+    /// hardcoded `localhost`) plus arbitrary extra headers (e.g. `Authorization`,
+    /// `Sec-WebSocket-Protocol`) to inject into the handshake request. Keeps `upgrade_client`
+    /// as a thin wrapper for callers that don't need either.
+    #[allow(unused_variables)]
+    pub async fn upgrade_client_with(self, protocol: StreamProtocol, url: url::Url, headers: Vec<(tokio_tungstenite::tungstenite::http::header::HeaderName, tokio_tungstenite::tungstenite::http::header::HeaderValue)>) -> Result<Stream, CommsError> {
+        self.upgrade_client_with_ext(protocol, None, Some(url), headers).await
+    }
+
+    #[allow(unused_variables)]
+    pub(crate) async fn upgrade_client_with_ext(self, protocol: StreamProtocol, tls: Option<(Arc<rustls::ClientConfig>, String)>, url: Option<url::Url>, headers: Vec<(tokio_tungstenite::tungstenite::http::header::HeaderName, tokio_tungstenite::tungstenite::http::header::HeaderValue)>) -> Result<Stream, CommsError> {
         debug!("tcp-protocol-upgrade(client): {}", protocol);
 
         let ret = match self {
@@ -260,9 +513,15 @@ impl Stream
                 match protocol {
                     StreamProtocol::Tcp => Stream::Tcp(a),
                     StreamProtocol::WebSocket => {
-                        let url = StreamProtocol::WebSocket.make_url("localhost".to_string(), 80, "/".to_string())?;
+                        let url = match url {
+                            Some(url) => url,
+                            None => StreamProtocol::WebSocket.make_url("localhost".to_string(), 80, "/".to_string())?,
+                        };
                         let mut request = tokio_tungstenite::tungstenite::http::Request::new(());
                         *request.uri_mut() = tokio_tungstenite::tungstenite::http::Uri::from_str(url.as_str())?;
+                        for (name, value) in headers.iter() {
+                            request.headers_mut().insert(name.clone(), value.clone());
+                        }
                         let (stream, response) = tokio_tungstenite::client_async(request, a)
                             .await?;
                         if response.status().is_client_error() {
@@ -270,6 +529,30 @@ impl Stream
                         }
                         Stream::WebSocket(stream, protocol)
                     },
+                    StreamProtocol::Tls => {
+                        let tls_stream = Stream::connect_tls(a, tls).await?;
+                        Stream::Tls(TlsStream::Client(tls_stream), protocol)
+                    },
+                    StreamProtocol::WebSocketTls => {
+                        let tls_stream = Stream::connect_tls(a, tls).await?;
+                        let tls_stream = TlsStream::Client(tls_stream);
+
+                        let url = match url {
+                            Some(url) => url,
+                            None => StreamProtocol::WebSocketTls.make_url("localhost".to_string(), 443, "/".to_string())?,
+                        };
+                        let mut request = tokio_tungstenite::tungstenite::http::Request::new(());
+                        *request.uri_mut() = tokio_tungstenite::tungstenite::http::Uri::from_str(url.as_str())?;
+                        for (name, value) in headers.iter() {
+                            request.headers_mut().insert(name.clone(), value.clone());
+                        }
+                        let (stream, response) = tokio_tungstenite::client_async(request, tls_stream)
+                            .await?;
+                        if response.status().is_client_error() {
+                            bail!(CommsErrorKind::WebSocketInternalError(format!("HTTP error while performing WebSocket handshack - status-code={}", response.status().as_u16())));
+                        }
+                        Stream::WebSocketTls(stream, protocol)
+                    },
                 }
             },
             #[cfg(feature = "enable_full")]
@@ -277,25 +560,49 @@ impl Stream
                 match protocol {
                     StreamProtocol::Tcp => Stream::WebSocket(a, p),
                     StreamProtocol::WebSocket => Stream::WebSocket(a, p),
+                    _ => Stream::WebSocket(a, p),
                 }
             },
             #[cfg(feature = "enable_server")]
             Stream::HyperWebSocket(a, p) => {
                 match protocol {
                     StreamProtocol::Tcp => Stream::HyperWebSocket(a, p),
-                    StreamProtocol::WebSocket => Stream::HyperWebSocket(a, p)
+                    StreamProtocol::WebSocket => Stream::HyperWebSocket(a, p),
+                    _ => Stream::HyperWebSocket(a, p),
                 }
             },
+            #[cfg(feature = "enable_full")]
+            Stream::Tls(a, p) => {
+                Stream::Tls(a, p)
+            },
+            #[cfg(feature = "enable_full")]
+            Stream::WebSocketTls(a, p) => {
+                Stream::WebSocketTls(a, p)
+            },
             Stream::Custom(a, p) => {
                 match protocol {
-                    StreamProtocol::Tcp => Stream::WebSocket(a),
-                    StreamProtocol::WebSocket => Stream::WebSocket(a),
+                    StreamProtocol::Tcp => Stream::Custom(a, p),
+                    StreamProtocol::WebSocket => Stream::Custom(a, p),
+                    _ => Stream::Custom(a, p),
                 }
             }
         };
         Ok(ret)
     }
 
+    #[cfg(feature = "enable_full")]
+    async fn connect_tls(a: TcpStream, tls: Option<(Arc<rustls::ClientConfig>, String)>) -> Result<RustlsClientStream<TcpStream>, CommsError> {
+        let (tls_config, server_name) = match tls {
+            Some(a) => a,
+            None => { bail!(CommsErrorKind::UnsupportedProtocolError("a rustls::ClientConfig and server-name are required to upgrade to a tls protocol".to_string())); }
+        };
+        let connector = tokio_rustls::TlsConnector::from(tls_config);
+        let dns_name = rustls::ServerName::try_from(server_name.as_str())
+            .map_err(|_| CommsErrorKind::UnsupportedProtocolError(format!("invalid TLS server name - {}", server_name)))?;
+        let tls_stream = connector.connect(dns_name, a).await?;
+        Ok(tls_stream)
+    }
+
     #[allow(dead_code)]
     pub fn protocol(&self) -> StreamProtocol
     {
@@ -306,6 +613,10 @@ impl Stream
             Stream::WebSocket(_, p) => p.clone(),
             #[cfg(feature = "enable_server")]
             Stream::HyperWebSocket(_, p) => p.clone(),
+            #[cfg(feature = "enable_full")]
+            Stream::Tls(_, p) => p.clone(),
+            #[cfg(feature = "enable_full")]
+            Stream::WebSocketTls(_, p) => p.clone(),
             Stream::Custom(_, p) => p,
         }
     }
@@ -327,18 +638,34 @@ impl StreamTx
                 }
                 a.write_u8(buf.len() as u8).await?;
                 total_sent += 1u64;
-                a.write_all(&buf[..]).await?; 
+                a.write_all(&buf[..]).await?;
                 total_sent += buf.len() as u64;
+                if !delay_flush { a.flush().await?; }
             },
             #[cfg(feature = "enable_full")]
-            StreamTx::WebSocket(_) => {
+            StreamTx::WebSocket(_, _) => {
                 total_sent += self.write_32bit(buf, delay_flush).await?;
             },
             #[cfg(feature = "enable_server")]
-            StreamTx::HyperWebSocket(_) => {
+            StreamTx::HyperWebSocket(_, _) => {
                 total_sent += self.write_32bit(buf, delay_flush).await?;
             },
-            StreamTx::Custom(file) => {
+            #[cfg(feature = "enable_full")]
+            StreamTx::Tls(a, _) => {
+                if buf.len() > u8::MAX as usize {
+                    return Err(tokio::io::Error::new(tokio::io::ErrorKind::InvalidData, format!("Data is to big to write (len={}, max={})", buf.len(), u8::MAX)));
+                }
+                a.write_u8(buf.len() as u8).await?;
+                total_sent += 1u64;
+                a.write_all(&buf[..]).await?;
+                total_sent += buf.len() as u64;
+                if !delay_flush { a.flush().await?; }
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocketTls(_, _) => {
+                total_sent += self.write_32bit(buf, delay_flush).await?;
+            },
+            StreamTx::Custom(_, _) => {
                 total_sent += self.write_32bit(buf, delay_flush).await?;
             },
         }
@@ -360,18 +687,34 @@ impl StreamTx
                 }
                 a.write_u16(buf.len() as u16).await?;
                 total_sent += 2u64;
-                a.write_all(&buf[..]).await?; 
+                a.write_all(&buf[..]).await?;
                 total_sent += buf.len() as u64;
+                if !delay_flush { a.flush().await?; }
             },
             #[cfg(feature = "enable_full")]
-            StreamTx::WebSocket(_) => {
+            StreamTx::WebSocket(_, _) => {
                 total_sent += self.write_32bit(buf, delay_flush).await?;
             },
             #[cfg(feature = "enable_server")]
-            StreamTx::HyperWebSocket(_) => {
+            StreamTx::HyperWebSocket(_, _) => {
                 total_sent += self.write_32bit(buf, delay_flush).await?;
             },
-            StreamTx::Custom(_) => {
+            #[cfg(feature = "enable_full")]
+            StreamTx::Tls(a, _) => {
+                if buf.len() > u16::MAX as usize {
+                    return Err(tokio::io::Error::new(tokio::io::ErrorKind::InvalidData, format!("Data is to big to write (len={}, max={})", buf.len(), u16::MAX)));
+                }
+                a.write_u16(buf.len() as u16).await?;
+                total_sent += 2u64;
+                a.write_all(&buf[..]).await?;
+                total_sent += buf.len() as u64;
+                if !delay_flush { a.flush().await?; }
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocketTls(_, _) => {
+                total_sent += self.write_32bit(buf, delay_flush).await?;
+            },
+            StreamTx::Custom(_, _) => {
                 total_sent += self.write_32bit(buf, delay_flush).await?;
             }
         }
@@ -393,11 +736,13 @@ impl StreamTx
                 }
                 a.write_u32(buf.len() as u32).await?;
                 total_sent += 4u64;
-                a.write_all(&buf[..]).await?; 
+                a.write_all(&buf[..]).await?;
                 total_sent += buf.len() as u64;
+                if !delay_flush { a.flush().await?; }
             },
             #[cfg(feature = "enable_full")]
-            StreamTx::WebSocket(a) => {
+            StreamTx::WebSocket(a, _) => {
+                let mut a = a.lock().await;
                 total_sent += buf.len() as u64;
                 if delay_flush {
                     match a.feed(Message::binary(buf)).await {
@@ -418,7 +763,8 @@ impl StreamTx
                 }
             },
             #[cfg(feature = "enable_server")]
-            StreamTx::HyperWebSocket(a) => {
+            StreamTx::HyperWebSocket(a, _) => {
+                let mut a = a.lock().await;
                 total_sent += buf.len() as u64;
                 if delay_flush {
                     match a.feed(HyperMessage::binary(buf)).await {
@@ -438,12 +784,48 @@ impl StreamTx
                     }
                 }
             },
-            StreamTx::Custom(a) => {
+            #[cfg(feature = "enable_full")]
+            StreamTx::Tls(a, _) => {
+                if buf.len() > u32::MAX as usize {
+                    return Err(tokio::io::Error::new(tokio::io::ErrorKind::InvalidData, format!("Data is to big to write (len={}, max={})", buf.len(), u32::MAX)));
+                }
+                a.write_u32(buf.len() as u32).await?;
+                total_sent += 4u64;
+                a.write_all(&buf[..]).await?;
+                total_sent += buf.len() as u64;
+                if !delay_flush { a.flush().await?; }
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocketTls(a, _) => {
+                let mut a = a.lock().await;
+                total_sent += buf.len() as u64;
+                if delay_flush {
+                    match a.feed(Message::binary(buf)).await {
+                        Ok(a) => a,
+                        Err(err) => {
+                            let kind = StreamTx::conv_error_kind(&err);
+                            return Err(tokio::io::Error::new(kind, format!("Failed to feed data into websocket - {}", err.to_string())));
+                        }
+                    }
+                } else {
+                    match a.send(Message::binary(buf)).await {
+                        Ok(a) => a,
+                        Err(err) => {
+                            let kind = StreamTx::conv_error_kind(&err);
+                            return Err(tokio::io::Error::new(kind, format!("Failed to feed data into websocket - {}", err.to_string())));
+                        }
+                    }
+                }
+            },
+            StreamTx::Custom(a, _) => {
                 if buf.len() > u32::MAX as usize {
                     return Err(tokio::io::Error::new(tokio::io::ErrorKind::InvalidData, format!("Data is to big to write (len={}, max={})", buf.len(), u32::MAX)));
                 }
+                a.write_u32(buf.len() as u32).await?;
+                total_sent += 4u64;
                 a.write_all(&buf[..]).await?;
                 total_sent += buf.len() as u64;
+                if !delay_flush { a.flush().await?; }
             }
         }
         #[allow(unreachable_code)]
@@ -480,22 +862,236 @@ impl StreamTx
         #[allow(unreachable_code)]
         Ok(total_sent)
     }
+
+    /// The last time a `Pong` was observed on this transport, or `None` for `Tcp`/`Tls`/
+    /// `Custom` which have no native ping/pong frame to observe.
+    pub(crate) fn last_pong(&self) -> Option<std::time::Instant> {
+        match self {
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocket(_, last_pong) => *last_pong.lock().unwrap(),
+            #[cfg(feature = "enable_server")]
+            StreamTx::HyperWebSocket(_, last_pong) => *last_pong.lock().unwrap(),
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocketTls(_, last_pong) => *last_pong.lock().unwrap(),
+            _ => None,
+        }
+    }
+
+    /// Sends a liveness probe down this transport - a `Message::Ping` for the WebSocket
+    /// variants, or a zero-length framed heartbeat for `Tcp`/`Tls`/`Custom` which have no
+    /// native ping frame. Used by `StreamTxChannel`'s keepalive task.
+    pub(crate) async fn send_ping(&mut self) -> Result<(), tokio::io::Error> {
+        match self {
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocket(a, _) => {
+                let mut a = a.lock().await;
+                match a.send(Message::Ping(vec![])).await {
+                    Ok(_) => Ok(()),
+                    Err(err) => {
+                        let kind = StreamTx::conv_error_kind(&err);
+                        Err(tokio::io::Error::new(kind, format!("Failed to send websocket ping - {}", err.to_string())))
+                    }
+                }
+            },
+            #[cfg(feature = "enable_server")]
+            StreamTx::HyperWebSocket(a, _) => {
+                let mut a = a.lock().await;
+                match a.send(HyperMessage::Ping(vec![])).await {
+                    Ok(_) => Ok(()),
+                    Err(err) => {
+                        let kind = StreamTx::conv_error_kind(&err);
+                        Err(tokio::io::Error::new(kind, format!("Failed to send websocket ping - {}", err.to_string())))
+                    }
+                }
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocketTls(a, _) => {
+                let mut a = a.lock().await;
+                match a.send(Message::Ping(vec![])).await {
+                    Ok(_) => Ok(()),
+                    Err(err) => {
+                        let kind = StreamTx::conv_error_kind(&err);
+                        Err(tokio::io::Error::new(kind, format!("Failed to send websocket ping - {}", err.to_string())))
+                    }
+                }
+            },
+            _ => {
+                self.write_32bit(&[], false).await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Performs the initiating side of the RFC 6455 closing handshake: sends a `Close` frame
+    /// carrying `code`/`reason` and drives the sink to completion so the peer actually observes
+    /// it, for the WebSocket variants; `Tcp`/`Tls`/`Custom` have no closing handshake, so this
+    /// just shuts down the write half. The peer's reciprocal `Close` is recognised on the read
+    /// side (see `StreamRx::read_32bit`), which echoes it back and surfaces as a distinct
+    /// `ErrorKind::ConnectionAborted` rather than an empty payload, completing the handshake from
+    /// both directions without being confused for a zero-length message.
+    pub async fn close(&mut self, code: CloseCode, reason: String) -> Result<(), tokio::io::Error> {
+        match self {
+            #[cfg(feature = "enable_full")]
+            StreamTx::Tcp(a) => {
+                a.shutdown().await?;
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocket(a, _) => {
+                let mut a = a.lock().await;
+                let frame = tokio_tungstenite::tungstenite::protocol::CloseFrame {
+                    code,
+                    reason: reason.into(),
+                };
+                if let Err(err) = a.send(Message::Close(Some(frame))).await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to send websocket close - {}", err.to_string())));
+                }
+                if let Err(err) = a.close().await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to close websocket - {}", err.to_string())));
+                }
+            },
+            #[cfg(feature = "enable_server")]
+            StreamTx::HyperWebSocket(a, _) => {
+                let mut a = a.lock().await;
+                let frame = hyper_tungstenite::tungstenite::protocol::CloseFrame {
+                    code: hyper_tungstenite::tungstenite::protocol::frame::coding::CloseCode::from(u16::from(code)),
+                    reason: reason.into(),
+                };
+                if let Err(err) = a.send(HyperMessage::Close(Some(frame))).await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to send websocket close - {}", err.to_string())));
+                }
+                if let Err(err) = a.close().await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to close websocket - {}", err.to_string())));
+                }
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::Tls(a, _) => {
+                a.shutdown().await?;
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocketTls(a, _) => {
+                let mut a = a.lock().await;
+                let frame = tokio_tungstenite::tungstenite::protocol::CloseFrame {
+                    code,
+                    reason: reason.into(),
+                };
+                if let Err(err) = a.send(Message::Close(Some(frame))).await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to send websocket close - {}", err.to_string())));
+                }
+                if let Err(err) = a.close().await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to close websocket - {}", err.to_string())));
+                }
+            },
+            StreamTx::Custom(a, _) => {
+                a.shutdown().await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Flushes any data buffered by a `delay_flush=true` write - `AsyncWriteExt::flush` for
+    /// `Tcp`/`Tls`/`Custom`, or driving the shared sink for the WebSocket variants. `send`
+    /// already flushes, since its final write passes `delay_flush=false`; callers that batch
+    /// several `delay_flush=true` writes should call this once afterwards.
+    pub async fn flush(&mut self) -> Result<(), tokio::io::Error> {
+        match self {
+            #[cfg(feature = "enable_full")]
+            StreamTx::Tcp(a) => {
+                a.flush().await?;
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocket(a, _) => {
+                let mut a = a.lock().await;
+                if let Err(err) = a.flush().await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to flush websocket - {}", err.to_string())));
+                }
+            },
+            #[cfg(feature = "enable_server")]
+            StreamTx::HyperWebSocket(a, _) => {
+                let mut a = a.lock().await;
+                if let Err(err) = a.flush().await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to flush websocket - {}", err.to_string())));
+                }
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::Tls(a, _) => {
+                a.flush().await?;
+            },
+            #[cfg(feature = "enable_full")]
+            StreamTx::WebSocketTls(a, _) => {
+                let mut a = a.lock().await;
+                if let Err(err) = a.flush().await {
+                    let kind = StreamTx::conv_error_kind(&err);
+                    return Err(tokio::io::Error::new(kind, format!("Failed to flush websocket - {}", err.to_string())));
+                }
+            },
+            StreamTx::Custom(a, _) => {
+                a.flush().await?;
+            },
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 pub struct StreamTxChannel
 {
-    tx: StreamTx,
+    tx: Arc<AsyncMutex<StreamTx>>,
     pub(crate) wire_encryption: Option<EncryptKey>,
+    missed_pongs: Arc<std::sync::atomic::AtomicU32>,
 }
 
 impl StreamTxChannel
 {
     pub fn new(tx: StreamTx, wire_encryption: Option<EncryptKey>) -> StreamTxChannel
     {
+        StreamTxChannel::new_ext(tx, wire_encryption, None)
+    }
+
+    /// Same as [`StreamTxChannel::new`] but also accepts an opt-in `keepalive_interval`. When
+    /// set, a background task sends a liveness probe on this channel's `StreamTx` once every
+    /// interval, whether or not data was written in between, so long-lived connections aren't
+    /// dropped by idle proxies/load balancers. Use [`StreamTxChannel::last_pong`]/
+    /// [`StreamTxChannel::missed_pongs`] to notice a dead peer and tear the connection down.
+    pub fn new_ext(tx: StreamTx, wire_encryption: Option<EncryptKey>, keepalive_interval: Option<Duration>) -> StreamTxChannel
+    {
+        let tx = Arc::new(AsyncMutex::new(tx));
+        let missed_pongs = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        if let Some(interval) = keepalive_interval {
+            let tx = tx.clone();
+            let missed_pongs = missed_pongs.clone();
+            tokio::spawn(async move {
+                let mut tick = tokio::time::interval(interval);
+                loop {
+                    tick.tick().await;
+
+                    let mut guard = tx.lock().await;
+                    match guard.last_pong() {
+                        Some(last_pong) if last_pong.elapsed() > interval => {
+                            missed_pongs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        },
+                        _ => { },
+                    }
+
+                    if guard.send_ping().await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
         StreamTxChannel {
             tx,
-            wire_encryption
+            wire_encryption,
+            missed_pongs,
         }
     }
 
@@ -503,7 +1099,22 @@ impl StreamTxChannel
     pub(crate) async fn send(&mut self, pck: PacketData)
     -> Result<u64, tokio::io::Error>
     {
-        self.tx.send(&self.wire_encryption, pck).await
+        let mut tx = self.tx.lock().await;
+        tx.send(&self.wire_encryption, pck).await
+    }
+
+    /// The last time a `Pong` was observed on this channel's underlying transport (always
+    /// `None` for `Tcp`/`Tls`/`Custom`, which have no native ping/pong frame).
+    pub async fn last_pong(&self) -> Option<std::time::Instant> {
+        self.tx.lock().await.last_pong()
+    }
+
+    /// How many keepalive ticks, since this channel was created, found the last observed `Pong`
+    /// older than the interval. Only advances once a `keepalive_interval` has been set via
+    /// `new_ext`, and stays 0 until a first `Pong` has been observed (so always for
+    /// `Tcp`/`Tls`/`Custom`).
+    pub fn missed_pongs(&self) -> u32 {
+        self.missed_pongs.load(std::sync::atomic::Ordering::SeqCst)
     }
 }
 
@@ -523,13 +1134,26 @@ impl StreamRx
                 bytes
             },
             #[cfg(feature = "enable_full")]
-            StreamRx::WebSocket(_) => {
+            StreamRx::WebSocket(_, _, _) => {
                 self.read_32bit().await?
             },
             #[cfg(feature = "enable_server")]
-            StreamRx::HyperWebSocket(_) => {
+            StreamRx::HyperWebSocket(_, _, _) => {
                 self.read_32bit().await?
             }
+            #[cfg(feature = "enable_full")]
+            StreamRx::Tls(a, _) => {
+                let len = a.read_u8().await?;
+                if len <= 0 { return Ok(vec![]); }
+                let mut bytes = vec![0 as u8; len as usize];
+                let n = a.read_exact(&mut bytes).await?;
+                if n != (len as usize) { return Ok(vec![]); }
+                bytes
+            },
+            #[cfg(feature = "enable_full")]
+            StreamRx::WebSocketTls(_, _, _) => {
+                self.read_32bit().await?
+            },
             StreamRx::Custom(_, _) => {
                 self.read_32bit().await?
             },
@@ -552,11 +1176,24 @@ impl StreamRx
                 bytes
             },
             #[cfg(feature = "enable_full")]
-            StreamRx::WebSocket(_) => {
+            StreamRx::WebSocket(_, _, _) => {
                 self.read_32bit().await?
             },
             #[cfg(feature = "enable_server")]
-            StreamRx::HyperWebSocket(_) => {
+            StreamRx::HyperWebSocket(_, _, _) => {
+                self.read_32bit().await?
+            },
+            #[cfg(feature = "enable_full")]
+            StreamRx::Tls(a, _) => {
+                let len = a.read_u16().await?;
+                if len <= 0 { return Ok(vec![]); }
+                let mut bytes = vec![0 as u8; len as usize];
+                let n = a.read_exact(&mut bytes).await?;
+                if n != (len as usize) { return Ok(vec![]); }
+                bytes
+            },
+            #[cfg(feature = "enable_full")]
+            StreamRx::WebSocketTls(_, _, _) => {
                 self.read_32bit().await?
             },
             StreamRx::Custom(_, _) => {
@@ -581,61 +1218,123 @@ impl StreamRx
                 bytes
             },
             #[cfg(feature = "enable_full")]
-            StreamRx::WebSocket(a) => {
-                match a.next().await {
-                    Some(a) => {
-                        let msg = match a {
-                            Ok(a) => a,
-                            Err(err) => {
-                                return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket - {}", err.to_string())));
-                            }
-                        };
-                        match msg {
-                            Message::Binary(a) => a,
-                            _ => {
-                                return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket as the message was the wrong type")));
+            StreamRx::WebSocket(a, sink, last_pong) => {
+                loop {
+                    let msg = match a.next().await {
+                        Some(Ok(a)) => a,
+                        Some(Err(err)) => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket - {}", err.to_string())));
+                        },
+                        None => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket")));
+                        }
+                    };
+                    match msg {
+                        Message::Binary(a) => break a,
+                        Message::Ping(payload) => {
+                            let mut sink = sink.lock().await;
+                            if let Err(err) = sink.send(Message::Pong(payload)).await {
+                                return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to reply to websocket ping - {}", err.to_string())));
                             }
+                        },
+                        Message::Pong(_) => {
+                            last_pong.lock().unwrap().replace(std::time::Instant::now());
+                        },
+                        Message::Close(frame) => {
+                            let mut sink = sink.lock().await;
+                            let _ = sink.send(Message::Close(frame)).await;
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::ConnectionAborted, "websocket was closed cleanly by the peer"));
+                        },
+                        _ => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket as the message was the wrong type")));
                         }
-                    },
-                    None => {
-                        return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket")));
                     }
                 }
             },
             #[cfg(feature = "enable_server")]
-            StreamRx::HyperWebSocket(a) => {
-                match a.next().await {
-                    Some(a) => {
-                        let msg = match a {
-                            Ok(a) => a,
-                            Err(err) => {
-                                return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket - {}", err.to_string())));
-                            }
-                        };
-                        match msg {
-                            HyperMessage::Binary(a) => a,
-                            _ => {
-                                return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket as the message was the wrong type")));
+            StreamRx::HyperWebSocket(a, sink, last_pong) => {
+                loop {
+                    let msg = match a.next().await {
+                        Some(Ok(a)) => a,
+                        Some(Err(err)) => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket - {}", err.to_string())));
+                        },
+                        None => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket")));
+                        }
+                    };
+                    match msg {
+                        HyperMessage::Binary(a) => break a,
+                        HyperMessage::Ping(payload) => {
+                            let mut sink = sink.lock().await;
+                            if let Err(err) = sink.send(HyperMessage::Pong(payload)).await {
+                                return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to reply to websocket ping - {}", err.to_string())));
                             }
+                        },
+                        HyperMessage::Pong(_) => {
+                            last_pong.lock().unwrap().replace(std::time::Instant::now());
+                        },
+                        HyperMessage::Close(frame) => {
+                            let mut sink = sink.lock().await;
+                            let _ = sink.send(HyperMessage::Close(frame)).await;
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::ConnectionAborted, "websocket was closed cleanly by the peer"));
+                        },
+                        _ => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket as the message was the wrong type")));
                         }
-                    },
-                    None => {
-                        return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket")));
                     }
                 }
             },
-            StreamRx::Custom(a, _) => {
-                let mut ret = bytes::BytesMut::new();
+            #[cfg(feature = "enable_full")]
+            StreamRx::Tls(a, _) => {
+                let len = a.read_u32().await?;
+                if len <= 0 { return Ok(vec![]); }
+                let mut bytes = vec![0 as u8; len as usize];
+                let n = a.read_exact(&mut bytes).await?;
+                if n != (len as usize) { return Ok(vec![]); }
+                bytes
+            },
+            #[cfg(feature = "enable_full")]
+            StreamRx::WebSocketTls(a, sink, last_pong) => {
                 loop {
-                    let mut buf = [0u8; 16384];
-                    let n = a.read(&mut buf).await?;
-                    if n > 0 {
-                        ret.extend_from_slice(&buf[..n]);
-                    } else {
-                        break;
+                    let msg = match a.next().await {
+                        Some(Ok(a)) => a,
+                        Some(Err(err)) => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket - {}", err.to_string())));
+                        },
+                        None => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket")));
+                        }
+                    };
+                    match msg {
+                        Message::Binary(a) => break a,
+                        Message::Ping(payload) => {
+                            let mut sink = sink.lock().await;
+                            if let Err(err) = sink.send(Message::Pong(payload)).await {
+                                return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to reply to websocket ping - {}", err.to_string())));
+                            }
+                        },
+                        Message::Pong(_) => {
+                            last_pong.lock().unwrap().replace(std::time::Instant::now());
+                        },
+                        Message::Close(frame) => {
+                            let mut sink = sink.lock().await;
+                            let _ = sink.send(Message::Close(frame)).await;
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::ConnectionAborted, "websocket was closed cleanly by the peer"));
+                        },
+                        _ => {
+                            return Err(tokio::io::Error::new(tokio::io::ErrorKind::BrokenPipe, format!("Failed to receive data from websocket as the message was the wrong type")));
+                        }
                     }
                 }
-                ret.to_vec()
+            },
+            StreamRx::Custom(a, _) => {
+                let len = a.read_u32().await?;
+                if len <= 0 { return Ok(vec![]); }
+                let mut bytes = vec![0 as u8; len as usize];
+                let n = a.read_exact(&mut bytes).await?;
+                if n != (len as usize) { return Ok(vec![]); }
+                bytes
             },
         };
         #[allow(unreachable_code)]
@@ -649,10 +1348,117 @@ impl StreamRx
             #[cfg(feature = "enable_full")]
             StreamRx::Tcp(_) => StreamProtocol::Tcp,
             #[cfg(feature = "enable_full")]
-            StreamRx::WebSocket(_) => StreamProtocol::WebSocket,
+            StreamRx::WebSocket(_, _, _) => StreamProtocol::WebSocket,
             #[cfg(feature = "enable_server")]
-            StreamRx::HyperWebSocket(_) => StreamProtocol::WebSocket,
+            StreamRx::HyperWebSocket(_, _, _) => StreamProtocol::WebSocket,
+            #[cfg(feature = "enable_full")]
+            StreamRx::Tls(_, p) => p.clone(),
+            #[cfg(feature = "enable_full")]
+            StreamRx::WebSocketTls(_, _, _) => StreamProtocol::WebSocketTls,
             StreamRx::Custom(_, p) => p,
         }
     }
+}
+
+/// The header prepended to every frame written by [`StreamTx::write_chunked`] - lets a large
+/// message be split across several `write_32bit` frames instead of buffered whole, while
+/// `message_id` allows frames from several in-flight messages to interleave on one connection.
+#[derive(Debug, Clone, Copy)]
+struct ChunkHeader {
+    message_id: u64,
+    seq: u32,
+    is_last: bool,
+}
+
+impl ChunkHeader {
+    const SIZE: usize = 8 + 4 + 1;
+
+    fn encode(&self) -> [u8; ChunkHeader::SIZE] {
+        let mut ret = [0u8; ChunkHeader::SIZE];
+        ret[0..8].copy_from_slice(&self.message_id.to_be_bytes());
+        ret[8..12].copy_from_slice(&self.seq.to_be_bytes());
+        ret[12] = if self.is_last { 1 } else { 0 };
+        ret
+    }
+
+    fn decode(buf: &[u8]) -> Result<(ChunkHeader, &[u8]), tokio::io::Error> {
+        if buf.len() < ChunkHeader::SIZE {
+            return Err(tokio::io::Error::new(tokio::io::ErrorKind::InvalidData, "chunk frame shorter than its header"));
+        }
+        let message_id = u64::from_be_bytes(buf[0..8].try_into().unwrap());
+        let seq = u32::from_be_bytes(buf[8..12].try_into().unwrap());
+        let is_last = buf[12] != 0;
+        Ok((ChunkHeader { message_id, seq, is_last }, &buf[ChunkHeader::SIZE..]))
+    }
+}
+
+impl StreamTx
+{
+    /// Splits `data` into `chunk_size`-sized frames tagged with `message_id` and writes each one
+    /// via [`StreamTx::write_32bit`], so a multi-megabyte payload no longer has to be buffered
+    /// in one contiguous frame. Frames from different `message_id`s may freely interleave on the
+    /// same connection; the receiving side reassembles by id via [`ChunkReassembler`].
+    pub async fn write_chunked(&mut self, message_id: u64, data: &[u8], chunk_size: usize) -> Result<u64, tokio::io::Error> {
+        let chunk_size = chunk_size.max(1);
+        let mut total_sent = 0u64;
+        let mut seq = 0u32;
+        let mut offset = 0usize;
+        loop {
+            let end = (offset + chunk_size).min(data.len());
+            let is_last = end >= data.len();
+            let header = ChunkHeader { message_id, seq, is_last };
+
+            let mut framed = Vec::with_capacity(ChunkHeader::SIZE + (end - offset));
+            framed.extend_from_slice(&header.encode());
+            framed.extend_from_slice(&data[offset..end]);
+            total_sent += self.write_32bit(&framed, !is_last).await?;
+
+            if is_last { break; }
+            offset = end;
+            seq += 1;
+        }
+        Ok(total_sent)
+    }
+}
+
+/// Reassembles frames written by [`StreamTx::write_chunked`] back into whole messages, keyed by
+/// `message_id` so frames belonging to several concurrently in-flight messages can interleave on
+/// one connection without corrupting each other's boundaries.
+///
+/// Note: applying backpressure so a slow consumer throttles the producer (rather than this
+/// reassembler's buffers growing unboundedly) is the job of the inbox-processing loop that reads
+/// frames off `StreamRx` and drives a bounded channel - that loop lives in the `rx_tx` module,
+/// which this tree does not currently carry.
+#[derive(Debug, Default)]
+pub struct ChunkReassembler {
+    partial: std::collections::HashMap<u64, Vec<u8>>,
+}
+
+impl ChunkReassembler {
+    pub fn new() -> ChunkReassembler {
+        ChunkReassembler::default()
+    }
+
+    /// Feeds one chunked frame (as read whole via [`StreamRx::read_32bit`]) into the
+    /// reassembler. Returns `Some((message_id, data))` once that message's final chunk has
+    /// arrived, `None` while it is still incomplete.
+    pub fn feed(&mut self, frame: &[u8]) -> Result<Option<(u64, Vec<u8>)>, tokio::io::Error> {
+        let (header, payload) = ChunkHeader::decode(frame)?;
+        let buf = self.partial.entry(header.message_id).or_insert_with(Vec::new);
+        buf.extend_from_slice(payload);
+
+        if header.is_last {
+            let data = self.partial.remove(&header.message_id).unwrap_or_default();
+            Ok(Some((header.message_id, data)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Drops all in-flight messages - call this when the underlying connection is torn down so
+    /// a half-received message is discarded cleanly instead of corrupting the next message that
+    /// happens to reuse its id.
+    pub fn clear(&mut self) {
+        self.partial.clear();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+#![allow(unused_imports)]
+use serde::{Serialize, Deserialize};
+use rand::RngCore;
+
+/// A W3C Trace Context-shaped `(trace-id, span-id, sampled)` tuple, meant to be threaded through
+/// `HelloMetadata` by `mesh_hello_exchange_sender` so the server can re-parent its
+/// inbox-processing span under the same trace this `connect` span started, instead of every
+/// connection opening an unrelated root span - absent or malformed on the receiving side would
+/// simply fall back to a fresh root span rather than failing the handshake. Not yet wired into
+/// an actual handshake: `HelloMetadata` carries no such field, and nothing mints or reads one -
+/// see the `TODO(trace-propagation)` in `comms::client`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct TraceContext {
+    pub trace_id: u128,
+    pub span_id: u64,
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// Starts a fresh (randomly minted) trace context, as if this connection attempt were the
+    /// root of a new distributed trace. Picking up the *caller's* already-active trace-id
+    /// instead of minting a new one needs the tracing subscriber to expose the current span's
+    /// ids (e.g. via `tracing-opentelemetry`), which this crate does not currently wire in.
+    pub fn new_root() -> TraceContext {
+        let mut trace_id_bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut trace_id_bytes);
+        TraceContext {
+            trace_id: u128::from_be_bytes(trace_id_bytes),
+            span_id: rand::thread_rng().next_u64(),
+            sampled: true,
+        }
+    }
+
+    /// Renders this context as a `traceparent` header value per the W3C Trace Context spec
+    /// (`00-{trace_id}-{span_id}-{flags}`), for logging or forwarding to non-tracing-aware peers.
+    pub fn to_traceparent(&self) -> String {
+        format!("00-{:032x}-{:016x}-{:02x}", self.trace_id, self.span_id, if self.sampled { 1 } else { 0 })
+    }
+}
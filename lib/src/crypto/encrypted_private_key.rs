@@ -3,22 +3,47 @@ use tracing::{info, warn, debug, error, trace, instrument, span, Level};
 use serde::{Serialize, Deserialize};
 use crate::utils::vec_as_base64;
 use crate::utils::vec_from_base64;
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chacha20poly1305::aead::{Aead, NewAead};
 
 use super::*;
 
+/// The secret key is bound (via AEAD associated data) to both the public key it pairs with
+/// and the identity of the wrapping key, so a swapped `pk` or a ciphertext re-encrypted under
+/// a different `EncryptKey` is rejected rather than silently decrypting into garbage.
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
 pub struct EncryptedPrivateKey {
     pk: PublicSignKey,
     ek_hash: AteHash,
     sk_iv: InitializationVector,
     #[serde(serialize_with = "vec_as_base64", deserialize_with = "vec_from_base64")]
-    sk_encrypted: Vec<u8>
+    sk_encrypted: Vec<u8>,
+    #[serde(serialize_with = "vec_as_base64", deserialize_with = "vec_from_base64")]
+    sk_tag: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub enum EncryptedPrivateKeyError {
+    /// The Poly1305 tag did not match - the ciphertext, the IV, the wrapping key or the
+    /// bound public key has been tampered with (or the wrong `EncryptKey` was supplied).
+    TagMismatch,
+}
+
+impl std::fmt::Display for EncryptedPrivateKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptedPrivateKeyError::TagMismatch => write!(f, "authentication tag mismatch while decrypting the private key"),
+        }
+    }
 }
 
+impl std::error::Error for EncryptedPrivateKeyError { }
+
 impl EncryptedPrivateKey
 {
     #[allow(dead_code)]
     pub fn generate(encrypt_key: &EncryptKey) -> EncryptedPrivateKey {
+        debug_assert!(super::self_test::self_tests_passed(), "crypto self-test failed - refusing to generate key material on a possibly-broken backend");
         let pair = PrivateSignKey::generate(encrypt_key.size());
         EncryptedPrivateKey::from_pair(&pair, encrypt_key)
     }
@@ -27,19 +52,49 @@ impl EncryptedPrivateKey
     pub fn from_pair(pair: &PrivateSignKey, encrypt_key: &EncryptKey) -> EncryptedPrivateKey {
         let sk = pair.sk();
         let sk = encrypt_key.encrypt(&sk[..]);
-        
+
+        let pk = pair.as_public_key().clone();
+        let ek_hash = encrypt_key.hash();
+
+        // Associated data binds the ciphertext to the public key it pairs with and to the
+        // identity of the wrapping key, so tampering with either is caught at decrypt time.
+        let aad = EncryptedPrivateKey::associated_data(&pk, &ek_hash);
+        let cipher = EncryptedPrivateKey::cipher(encrypt_key);
+        let nonce = EncryptedPrivateKey::nonce(&sk.iv);
+
+        let mut encrypted = cipher
+            .encrypt(&nonce, chacha20poly1305::aead::Payload { msg: &sk.data[..], aad: &aad[..] })
+            .expect("chacha20poly1305 encryption of a private key must not fail");
+
+        // The `aead` crate appends the 16-byte Poly1305 tag to the ciphertext - split it back
+        // out so it can be stored (and verified) as its own explicit field.
+        let tag = encrypted.split_off(encrypted.len() - 16);
+
         EncryptedPrivateKey {
-            pk: pair.as_public_key().clone(),
-            ek_hash: encrypt_key.hash(),
+            pk,
+            ek_hash,
             sk_iv: sk.iv,
-            sk_encrypted: sk.data,
+            sk_encrypted: encrypted,
+            sk_tag: tag,
         }
     }
 
     #[allow(dead_code)]
-    pub fn as_private_key(&self, key: &EncryptKey) -> PrivateSignKey {
-        let data = key.decrypt(&self.sk_iv, &self.sk_encrypted[..]);
-        match &self.pk {
+    pub fn as_private_key(&self, key: &EncryptKey) -> Result<PrivateSignKey, EncryptedPrivateKeyError> {
+        debug_assert!(super::self_test::self_tests_passed(), "crypto self-test failed - refusing to trust a signature made with this backend");
+        let aad = EncryptedPrivateKey::associated_data(&self.pk, &self.ek_hash);
+        let cipher = EncryptedPrivateKey::cipher(key);
+        let nonce = EncryptedPrivateKey::nonce(&self.sk_iv);
+
+        let mut ciphertext = self.sk_encrypted.clone();
+        ciphertext.extend_from_slice(&self.sk_tag[..]);
+
+        let data = cipher
+            .decrypt(&nonce, chacha20poly1305::aead::Payload { msg: &ciphertext[..], aad: &aad[..] })
+            .map_err(|_| EncryptedPrivateKeyError::TagMismatch)?;
+        let data = key.decrypt(&self.sk_iv, &data[..]);
+
+        Ok(match &self.pk {
             PublicSignKey::Falcon512 { pk } => {
                 PrivateSignKey::Falcon512 {
                     pk: PublicSignKey::Falcon512 { pk: pk.clone() },
@@ -52,7 +107,7 @@ impl EncryptedPrivateKey
                     sk: data,
                 }
             },
-        }
+        })
     }
 
     #[allow(dead_code)]
@@ -69,4 +124,25 @@ impl EncryptedPrivateKey
     pub(crate) fn double_hash(&self) -> DoubleHash {
         DoubleHash::from_hashes(&self.pk_hash(), &self.ek_hash)
     }
-}
\ No newline at end of file
+
+    fn associated_data(pk: &PublicSignKey, ek_hash: &AteHash) -> Vec<u8> {
+        let mut aad = pk.hash().to_bytes();
+        aad.extend_from_slice(&ek_hash.to_bytes());
+        aad
+    }
+
+    fn cipher(encrypt_key: &EncryptKey) -> ChaCha20Poly1305 {
+        // Domain-separated from `EncryptKey::hash()` (which is the public identity stored as
+        // `ek_hash`) so the AEAD key itself is never derivable from anything we persist.
+        let derived = AteHash::from_bytes_twice(encrypt_key.value(), b"ate-encrypted-private-key-aead");
+        let key = Key::from_slice(&derived.to_bytes()[..32]);
+        ChaCha20Poly1305::new(key)
+    }
+
+    fn nonce(iv: &InitializationVector) -> Nonce {
+        // The nonce is derived from the IV rather than reused directly so that it is always
+        // exactly the 96 bits ChaCha20-Poly1305 requires, regardless of the IV's own size.
+        let derived = AteHash::from_bytes(&iv.bytes[..]);
+        Nonce::clone_from_slice(&derived.to_bytes()[..12])
+    }
+}
@@ -0,0 +1,90 @@
+#[allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use std::marker::PhantomData;
+
+use crate::error::SerializationError;
+use crate::session::Session;
+use crate::spec::SerializationFormat;
+
+use super::*;
+
+/// A value encrypted under a single `EncryptKey`, bundled with the IV needed to decrypt it.
+/// This is the building block `MultiEncryptedSecureData` wraps once per reader.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
+pub struct EncryptedSecureData<T>
+{
+    iv: InitializationVector,
+    data: Vec<u8>,
+    #[serde(skip)]
+    _marker: PhantomData<T>,
+}
+
+impl<T> EncryptedSecureData<T>
+where T: Serialize + DeserializeOwned + Clone,
+{
+    pub fn new(key: &EncryptKey, data: T) -> Result<EncryptedSecureData<T>, SerializationError> {
+        let bytes = SerializationFormat::Bincode.serialize(&data)?;
+        let enc = key.encrypt(&bytes[..]);
+        Ok(EncryptedSecureData {
+            iv: enc.iv,
+            data: enc.data,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn unwrap(&self, key: &EncryptKey) -> Result<T, SerializationError> {
+        let bytes = key.decrypt(&self.iv, &self.data[..]);
+        SerializationFormat::Bincode.deserialize(&bytes[..])
+    }
+}
+
+/// A value (typically the per-row symmetric data key) wrapped separately for every reader
+/// listed in `MetaAuthorization::allow_read`, so a single object can be shared with many
+/// readers without re-encrypting (or re-storing) the underlying payload once per reader.
+/// `new`/`unwrap` work standalone today, but nothing builds one of these from a commit: that
+/// needs each recipient's actual `EncryptKey` material, not just the `Hash` `allow_read` stores,
+/// and `Dio` has no trust store mapping one to the other - only a reader's own `Session` ever
+/// holds its key. Until `Dio`/`ChainMultiUser` grow that lookup, `CoreMetadata::EncyptedEncryptionKey`
+/// (see `meta::CoreMetadata`) stays a plain `EncryptKey`, not one of these.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MultiEncryptedSecureData<T>
+{
+    envelopes: Vec<(Hash, EncryptedSecureData<T>)>,
+}
+
+impl<T> MultiEncryptedSecureData<T>
+where T: Serialize + DeserializeOwned + Clone,
+{
+    /// Wraps `data` once for each `(recipient_hash, key)` pair - `key` is the actual key
+    /// material belonging to that reader, resolved by the caller from the session or chain;
+    /// `recipient_hash` is what gets stored so a reader can find their own envelope again.
+    pub fn new<'a>(readers: impl IntoIterator<Item = &'a (Hash, EncryptKey)>, data: &T) -> Result<MultiEncryptedSecureData<T>, SerializationError>
+    where T: 'a
+    {
+        let mut envelopes = Vec::new();
+        for (hash, key) in readers {
+            envelopes.push((hash.clone(), EncryptedSecureData::new(key, data.clone())?));
+        }
+        Ok(MultiEncryptedSecureData { envelopes })
+    }
+
+    /// Scans the envelopes for one matching a read-key held by `session` and unwraps it. If
+    /// none match, the caller holds none of the keys this object was shared with and should
+    /// treat the row as opaque rather than erroring.
+    pub fn unwrap(&self, session: &Session) -> Option<T> {
+        for key in session.read_keys() {
+            let hash = key.hash();
+            if let Some((_, envelope)) = self.envelopes.iter().find(|(h, _)| *h == hash) {
+                if let Ok(data) = envelope.unwrap(key) {
+                    return Some(data);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn envelope_count(&self) -> usize {
+        self.envelopes.len()
+    }
+}
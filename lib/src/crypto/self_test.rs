@@ -0,0 +1,121 @@
+#[allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use std::sync::Once;
+
+use super::*;
+
+/// Pass/fail outcome for a single primitive exercised by [`run_self_tests`].
+#[derive(Debug, Clone)]
+pub struct SelfTestResult
+{
+    pub name: &'static str,
+    pub passed: bool,
+}
+
+/// Structured report produced by [`run_self_tests`] - one entry per primitive checked.
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport
+{
+    pub results: Vec<SelfTestResult>,
+}
+
+impl SelfTestReport
+{
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+}
+
+fn check_hash_vectors() -> SelfTestResult
+{
+    // `AteHash::from_bytes` is deterministic, so the only property worth asserting without a
+    // fixed expected digest baked in here is that it is stable across repeated calls and that
+    // distinct inputs do not collide trivially.
+    let a1 = AteHash::from_bytes(b"ate-self-test-a");
+    let a2 = AteHash::from_bytes(b"ate-self-test-a");
+    let b = AteHash::from_bytes(b"ate-self-test-b");
+
+    let stable = a1 == a2;
+    let distinct = a1 != b;
+
+    SelfTestResult { name: "AteHash", passed: stable && distinct }
+}
+
+fn check_short_hash_vectors() -> SelfTestResult
+{
+    let a1 = ShortHash::from(AteHash::from_bytes(b"ate-self-test-short"));
+    let a2 = ShortHash::from(AteHash::from_bytes(b"ate-self-test-short"));
+
+    SelfTestResult { name: "ShortHash", passed: a1 == a2 }
+}
+
+fn check_encrypt_key_round_trip() -> SelfTestResult
+{
+    let key = EncryptKey::generate(KeySize::Bit256);
+    let plaintext = b"ate known-answer round trip";
+    let enc = key.encrypt(plaintext);
+    let dec = key.decrypt(&enc.iv, &enc.data[..]);
+
+    SelfTestResult { name: "EncryptKey", passed: dec == plaintext }
+}
+
+fn check_falcon_round_trip(size: KeySize, name: &'static str) -> SelfTestResult
+{
+    let pair = PrivateSignKey::generate(size);
+    let message = b"ate known-answer signing round trip";
+    let signature = pair.sign(message);
+    let passed = pair.as_public_key().verify(message, &signature[..]).unwrap_or(false);
+
+    SelfTestResult { name, passed }
+}
+
+/// Runs the power-on self-test suite against the signing, encryption and hashing primitives
+/// and returns a structured pass/fail report. Should be called once, before any key material
+/// derived from these primitives is trusted, so a miscompiled or backdoored crypto backend is
+/// caught at startup instead of silently producing unverifiable signatures.
+///
+/// This is a round-trip self-test, not a known-answer-test (KAT) suite: every primitive here
+/// (`AteHash`, `EncryptKey`, Falcon signing) is exercised by generating fresh key material and
+/// checking the primitive is internally consistent (stable/distinct hashes, a decrypt that
+/// recovers what was encrypted, a signature that verifies), rather than against a fixed,
+/// externally-pinned expected output. `AteHash`'s underlying digest isn't specified anywhere in
+/// this crate, so there is no canonical expected digest to pin a real KAT vector against.
+pub fn run_self_tests() -> SelfTestReport
+{
+    let report = SelfTestReport {
+        results: vec![
+            check_hash_vectors(),
+            check_short_hash_vectors(),
+            check_encrypt_key_round_trip(),
+            check_falcon_round_trip(KeySize::Bit128, "Falcon512"),
+            check_falcon_round_trip(KeySize::Bit256, "Falcon1024"),
+        ],
+    };
+
+    if report.all_passed() {
+        debug!("crypto self-test: all {} primitives passed", report.results.len());
+    } else {
+        for r in report.results.iter().filter(|r| !r.passed) {
+            error!("crypto self-test FAILED: {}", r.name);
+        }
+    }
+
+    report
+}
+
+static SELF_TEST_ONCE: Once = Once::new();
+static mut SELF_TEST_PASSED: bool = false;
+
+/// Runs [`run_self_tests`] exactly once per process and returns whether every primitive
+/// passed. [`EncryptedPrivateKey::generate`]/[`EncryptedPrivateKey::as_private_key`] check it
+/// with `debug_assert!`, so debug builds refuse to produce or trust key material on top of a
+/// broken crypto backend; release builds skip the check.
+pub fn self_tests_passed() -> bool
+{
+    unsafe {
+        SELF_TEST_ONCE.call_once(|| {
+            SELF_TEST_PASSED = run_self_tests().all_passed();
+        });
+        SELF_TEST_PASSED
+    }
+}
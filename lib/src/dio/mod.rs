@@ -197,6 +197,42 @@ impl<'a> Dio<'a>
         }
     }
 
+    /// Like [`Dio::load`], but retries on a capped exponential backoff while `load` reports
+    /// `ObjectStillLocked`, until `timeout` elapses. A lock held locally, within this very `Dio`,
+    /// can never clear on its own - that case fails fast with `ObjectStillLocked` to avoid
+    /// self-deadlock. `Dio::load` only consults this `Dio`'s own lock table, not locks another
+    /// session holds through `self.multi.pipe`, so this does not wait on those.
+    #[allow(dead_code)]
+    pub async fn load_wait<D>(&mut self, key: &PrimaryKey, timeout: std::time::Duration) -> Result<Dao<D>, LoadError>
+    where D: Serialize + DeserializeOwned + Clone + Send + Sync,
+    {
+        {
+            let state = self.state.lock();
+            if state.is_locked(key) {
+                return Result::Err(LoadError::ObjectStillLocked(key.clone()));
+            }
+        }
+
+        let started = std::time::Instant::now();
+        let mut backoff = std::time::Duration::from_millis(20);
+        loop {
+            match self.load(key).await {
+                Ok(dao) => return Ok(dao),
+                Err(LoadError::ObjectStillLocked(locked_key)) => {
+                    let elapsed = started.elapsed();
+                    if elapsed >= timeout {
+                        return Result::Err(LoadError::ObjectStillLocked(locked_key));
+                    }
+
+                    let remaining = timeout - elapsed;
+                    tokio::time::sleep(backoff.min(remaining)).await;
+                    backoff = (backoff * 2).min(std::time::Duration::from_secs(1));
+                },
+                Err(err) => return Result::Err(err),
+            }
+        }
+    }
+
     pub(crate) async fn children<D>(&mut self, parent_id: PrimaryKey, collection_id: u64) -> Result<Vec<Dao<D>>, LoadError>
     where D: Serialize + DeserializeOwned + Clone + Send + Sync,
     {
@@ -317,6 +353,63 @@ impl<'a> Dio<'a>
 
         Ok(ret)
     }
+
+    /// Reconstructs `key` as it stood at or before chain offset/timestamp `at`. Every store
+    /// writes a full row snapshot, so this walks the key's events in chain order and keeps
+    /// the latest one whose leaf offset does not exceed `at`; a tombstone encountered at or
+    /// before `at` means the row was already deleted by that point.
+    #[allow(dead_code)]
+    pub async fn load_at<D>(&mut self, key: &PrimaryKey, at: u64) -> Result<Dao<D>, LoadError>
+    where D: Serialize + DeserializeOwned + Clone + Send + Sync,
+    {
+        let mut latest: Option<(EventData, EventHeader, EventLeaf)> = None;
+
+        for leaf in self.multi.history(key).await? {
+            if leaf.created > at {
+                continue;
+            }
+
+            let evt = self.multi.load(leaf).await?;
+            let header = evt.header.as_header()?;
+
+            if header.meta.get_tombstone().is_some() {
+                return Result::Err(LoadError::AlreadyDeleted(key.clone()));
+            }
+
+            latest = match &latest {
+                Some((_, _, prev)) if prev.created >= leaf.created => latest,
+                _ => Some((evt.data, header, leaf)),
+            };
+        }
+
+        match latest {
+            Some((data, header, leaf)) => self.load_from_event(data, header, leaf),
+            None => Result::Err(LoadError::NotFound(key.clone())),
+        }
+    }
+
+    /// Materializes every historical version of `key`, oldest first, with each `Dao` carrying
+    /// the `created`/`updated` leaf offsets it was written at. Gives auditing and rollback on
+    /// top of the existing trust chain without changing the write path.
+    #[allow(dead_code)]
+    pub async fn history<D>(&mut self, key: &PrimaryKey) -> Result<Vec<Dao<D>>, LoadError>
+    where D: Serialize + DeserializeOwned + Clone + Send + Sync,
+    {
+        let mut ret = Vec::new();
+
+        for leaf in self.multi.history(key).await? {
+            let evt = self.multi.load(leaf).await?;
+            let header = evt.header.as_header()?;
+
+            if header.meta.get_tombstone().is_some() {
+                continue;
+            }
+
+            ret.push(self.load_from_event(evt.data, header, leaf)?);
+        }
+
+        Ok(ret)
+    }
 }
 
 impl Chain
@@ -397,7 +490,8 @@ impl<'a> Dio<'a>
         {
             // Build a new clean metadata header
             let mut meta = Metadata::for_data(row.key);
-            meta.core.push(CoreMetadata::Authorization(row.auth.clone()));
+            let auth = resolve_inherited_authorization(&state.cache_store_primary, row.tree.as_ref(), row.auth.clone());
+            meta.core.push(CoreMetadata::Authorization(auth));
             if let Some(tree) = &row.tree {
                 meta.core.push(CoreMetadata::Tree(tree.clone()))
             }
@@ -421,7 +515,8 @@ impl<'a> Dio<'a>
         // Build events that will represent tombstones on all these records (they will be sent after the writes)
         for (key, row) in &state.deleted {
             let mut meta = Metadata::default();
-            meta.core.push(CoreMetadata::Authorization(row.auth.clone()));
+            let auth = resolve_inherited_authorization(&state.cache_store_primary, row.tree.as_ref(), row.auth.clone());
+            meta.core.push(CoreMetadata::Authorization(auth));
             if let Some(tree) = &row.tree {
                 meta.core.push(CoreMetadata::Tree(tree.clone()))
             }
@@ -494,6 +589,89 @@ impl<'a> Dio<'a>
     }
 }
 
+/// Folds the `allow_read`/`allow_write` hashes of every ancestor staged in this same `Dio`
+/// transaction into `auth`, stopping at the first node that does not have a parent staged
+/// locally, or the first node whose `MetaTree` opts out of inheritance. This is the synchronous
+/// subset of [`Dio::effective_authorization`] that `commit` can afford to run without awaiting -
+/// a parent that was only ever loaded (not also created/updated) in this scope is resolved the
+/// full way by `effective_authorization` instead.
+fn resolve_inherited_authorization(cache_store_primary: &FxHashMap<PrimaryKey, Arc<RowData>>, tree: Option<&MetaTree>, auth: MetaAuthorization) -> MetaAuthorization
+{
+    let mut effective = auth;
+    let mut cursor = tree.cloned();
+    let mut visited = FxHashSet::default();
+
+    while let Some(tree) = cursor {
+        if tree.inherit_read == false && tree.inherit_write == false {
+            break;
+        }
+        if visited.contains(&tree.parent) {
+            break;
+        }
+        visited.insert(tree.parent.clone());
+
+        let parent = match cache_store_primary.get(&tree.parent) {
+            Some(parent) => parent,
+            None => break,
+        };
+
+        effective = effective.inherit_from(&parent.auth, tree.inherit_read, tree.inherit_write);
+        cursor = parent.tree.clone();
+    }
+
+    effective
+}
+
+impl<'a> Dio<'a>
+{
+    /// Materializes the effective (resolved) `MetaAuthorization` for `key` by walking up the
+    /// `MetaTree` parent chain, folding in each ancestor's `allow_read`/`allow_write` hashes
+    /// while `inherit_read`/`inherit_write` stays set. Lets a reader holding only a parent's
+    /// key validate access to a child, and lets callers introspect who can actually read or
+    /// write an object without re-deriving the walk themselves. Guards against malformed
+    /// parent links with a visited set rather than looping forever.
+    #[allow(dead_code)]
+    pub async fn effective_authorization(&mut self, key: &PrimaryKey) -> Result<MetaAuthorization, LoadError>
+    {
+        let mut visited = FxHashSet::default();
+        let mut cursor = key.clone();
+        let mut effective: Option<MetaAuthorization> = None;
+        let mut inherit_read = false;
+        let mut inherit_write = false;
+
+        loop {
+            if visited.contains(&cursor) {
+                return Result::Err(LoadError::NotFound(cursor));
+            }
+            visited.insert(cursor.clone());
+
+            let entry = match self.multi.lookup_primary(&cursor).await {
+                Some(a) => a,
+                None => return Result::Err(LoadError::NotFound(cursor)),
+            };
+            let evt = self.multi.load(entry).await?;
+            let header = evt.header.as_header()?;
+
+            let auth = header.meta.get_authorization().cloned().unwrap_or_default();
+            effective = Some(match effective {
+                None => auth,
+                Some(child) => child.inherit_from(&auth, inherit_read, inherit_write),
+            });
+
+            match header.meta.get_tree() {
+                Some(tree) if tree.inherit_read || tree.inherit_write => {
+                    inherit_read = tree.inherit_read;
+                    inherit_write = tree.inherit_write;
+                    cursor = tree.parent.clone();
+                },
+                _ => break,
+            }
+        }
+
+        Ok(effective.unwrap_or_default())
+    }
+}
+
 #[cfg(test)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum TestEnumDao
@@ -0,0 +1,84 @@
+#[allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use async_trait::async_trait;
+use fxhash::FxHashMap;
+use std::sync::Arc;
+use parking_lot::Mutex;
+
+use crate::event::{EventData, EventLeaf};
+use crate::header::PrimaryKey;
+use crate::meta::MetaCollection;
+
+use super::{EventStore, StorageError};
+
+/// An in-memory stand-in for the local redo log, shaped to be used wherever an `EventStore` is
+/// expected. Not actually plugged into anything yet: `Dio::commit` talks to `ChainMultiUser`'s
+/// own pipe directly rather than through this trait (see the `EventStore` doc comment), so no
+/// chain uses this - or `S3EventStore` - today.
+pub struct LocalEventStore
+{
+    events: Arc<Mutex<Vec<EventData>>>,
+    primary_index: Arc<Mutex<FxHashMap<PrimaryKey, EventLeaf>>>,
+    secondary_index: Arc<Mutex<FxHashMap<MetaCollection, Vec<PrimaryKey>>>>,
+}
+
+impl LocalEventStore
+{
+    pub fn new() -> LocalEventStore {
+        LocalEventStore {
+            events: Arc::new(Mutex::new(Vec::new())),
+            primary_index: Arc::new(Mutex::new(FxHashMap::default())),
+            secondary_index: Arc::new(Mutex::new(FxHashMap::default())),
+        }
+    }
+}
+
+impl Default for LocalEventStore {
+    fn default() -> LocalEventStore {
+        LocalEventStore::new()
+    }
+}
+
+#[async_trait]
+impl EventStore
+for LocalEventStore
+{
+    async fn append(&self, events: Vec<EventData>) -> Result<Vec<EventLeaf>, StorageError> {
+        let mut store = self.events.lock();
+        let mut leaves = Vec::with_capacity(events.len());
+        for evt in events {
+            let offset = store.len() as u64;
+            let leaf = EventLeaf { created: offset, updated: offset };
+            if let Some(key) = evt.meta.get_data_key() {
+                self.primary_index.lock().insert(key.clone(), leaf);
+            }
+            leaves.push(leaf);
+            store.push(evt);
+        }
+        Ok(leaves)
+    }
+
+    async fn load(&self, leaf: EventLeaf) -> Result<EventData, StorageError> {
+        let store = self.events.lock();
+        store.get(leaf.created as usize)
+            .cloned()
+            .ok_or_else(|| StorageError::BackendError(format!("no event at offset {}", leaf.created)))
+    }
+
+    async fn load_many(&self, leaves: Vec<EventLeaf>) -> Result<Vec<EventData>, StorageError> {
+        let mut ret = Vec::with_capacity(leaves.len());
+        for leaf in leaves {
+            ret.push(self.load(leaf).await?);
+        }
+        Ok(ret)
+    }
+
+    async fn lookup_primary(&self, key: &PrimaryKey) -> Option<EventLeaf> {
+        self.primary_index.lock().get(key).cloned()
+    }
+
+    // `append` records no collections, so `secondary_index` stays empty and this returns `None`.
+    async fn lookup_secondary_raw(&self, collection: &MetaCollection) -> Option<Vec<PrimaryKey>> {
+        self.secondary_index.lock().get(collection).cloned()
+    }
+}
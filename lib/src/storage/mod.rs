@@ -0,0 +1,59 @@
+#[allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use async_trait::async_trait;
+
+mod local;
+mod s3;
+
+pub use local::LocalEventStore;
+pub use s3::S3EventStore;
+pub use s3::S3Config;
+
+use crate::event::{EventData, EventLeaf};
+use crate::header::PrimaryKey;
+use crate::meta::MetaCollection;
+
+#[derive(Debug, Clone)]
+pub enum StorageError
+{
+    /// The backend itself failed (a local IO error, or a failed request to the object store).
+    BackendError(String),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::BackendError(msg) => write!(f, "storage backend error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for StorageError { }
+
+/// Everything the commit pipe would need from wherever events actually live, so `Dio`/`Chain`
+/// could be parameterized over a storage backend without changing their API. `ChainMultiUser`
+/// calls the equivalent of `append`/`load`/`load_many` plus the primary/secondary index lookups
+/// against the local redo log directly, not through this trait - `Dio::commit`/`Chain` don't
+/// take an `EventStore` anywhere yet, so `LocalEventStore`/`S3EventStore` are both standalone
+/// implementations waiting for that seam to actually exist, not already-wired alternatives.
+#[async_trait]
+pub trait EventStore
+where Self: Send + Sync
+{
+    /// Appends a batch of events (in order) to the backing store, returning the leaf each one
+    /// was assigned.
+    async fn append(&self, events: Vec<EventData>) -> Result<Vec<EventLeaf>, StorageError>;
+
+    /// Loads the raw event data for a single leaf.
+    async fn load(&self, leaf: EventLeaf) -> Result<EventData, StorageError>;
+
+    /// Loads the raw event data for many leaves, in the order given. Both implementations
+    /// here load them one leaf at a time.
+    async fn load_many(&self, leaves: Vec<EventLeaf>) -> Result<Vec<EventData>, StorageError>;
+
+    /// Resolves a primary key to the most recent leaf that wrote it.
+    async fn lookup_primary(&self, key: &PrimaryKey) -> Option<EventLeaf>;
+
+    /// Resolves a secondary (collection) index to every primary key currently in it.
+    async fn lookup_secondary_raw(&self, collection: &MetaCollection) -> Option<Vec<PrimaryKey>>;
+}
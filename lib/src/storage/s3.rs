@@ -0,0 +1,157 @@
+#[allow(unused_imports)]
+use tracing::{info, warn, debug, error, trace, instrument, span, Level};
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+
+use crate::event::{EventData, EventLeaf};
+use crate::header::PrimaryKey;
+use crate::meta::MetaCollection;
+use crate::spec::SerializationFormat;
+
+use super::{EventStore, StorageError};
+
+/// Where a chain's events live in an S3-compatible object store - one Bincode object per event
+/// under `<prefix>/events/<offset>`, plus one small Bincode object per primary key under
+/// `<prefix>/index/primary/<key>`. No secondary (collection) index is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config
+{
+    pub endpoint: String,
+    pub bucket: String,
+    pub prefix: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+/// An `EventStore` backed by an S3-compatible object store, so large/cold chains can offload
+/// their event segments instead of keeping everything in the local redo log. Nothing in
+/// `Dio`/`Chain` uses it: neither takes an `EventStore` (see the trait's doc comment).
+pub struct S3EventStore
+{
+    config: S3Config,
+    client: reqwest::Client,
+}
+
+impl S3EventStore
+{
+    pub fn new(config: S3Config) -> S3EventStore {
+        S3EventStore {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn object_key(&self, offset: u64) -> String {
+        format!("{}/events/{:020}", self.config.prefix, offset)
+    }
+
+    fn index_key(&self, key: &PrimaryKey) -> String {
+        format!("{}/index/primary/{}", self.config.prefix, key.as_hex_string())
+    }
+
+    fn object_url(&self, object_key: &str) -> String {
+        format!("{}/{}/{}", self.config.endpoint, self.config.bucket, object_key)
+    }
+
+    fn counter_key(&self) -> String {
+        format!("{}/events/_offset_counter", self.config.prefix)
+    }
+}
+
+#[async_trait]
+impl EventStore
+for S3EventStore
+{
+    async fn append(&self, events: Vec<EventData>) -> Result<Vec<EventLeaf>, StorageError> {
+        let mut leaves = Vec::with_capacity(events.len());
+
+        for evt in events {
+            let bytes = SerializationFormat::Bincode.serialize(&evt)
+                .map_err(|err| StorageError::BackendError(err.to_string()))?;
+
+            // The offset comes from a counter object stored next to the events (see
+            // `next_offset`), which is not safe against two writers appending at once.
+            let offset = self.next_offset().await?;
+            let url = self.object_url(&self.object_key(offset));
+
+            self.client.put(&url).body(bytes).send().await
+                .map_err(|err| StorageError::BackendError(err.to_string()))?;
+
+            if let Some(key) = evt.meta.get_data_key() {
+                let index_url = self.object_url(&self.index_key(&key));
+                let leaf = EventLeaf { created: offset, updated: offset };
+                let index_bytes = SerializationFormat::Bincode.serialize(&leaf)
+                    .map_err(|err| StorageError::BackendError(err.to_string()))?;
+                self.client.put(&index_url).body(index_bytes).send().await
+                    .map_err(|err| StorageError::BackendError(err.to_string()))?;
+            }
+
+            leaves.push(EventLeaf { created: offset, updated: offset });
+        }
+
+        Ok(leaves)
+    }
+
+    async fn load(&self, leaf: EventLeaf) -> Result<EventData, StorageError> {
+        let url = self.object_url(&self.object_key(leaf.created));
+        let bytes = self.client.get(&url).send().await
+            .map_err(|err| StorageError::BackendError(err.to_string()))?
+            .bytes().await
+            .map_err(|err| StorageError::BackendError(err.to_string()))?;
+
+        SerializationFormat::Bincode.deserialize(&bytes[..])
+            .map_err(|err| StorageError::BackendError(err.to_string()))
+    }
+
+    async fn load_many(&self, leaves: Vec<EventLeaf>) -> Result<Vec<EventData>, StorageError> {
+        let mut ret = Vec::with_capacity(leaves.len());
+        for leaf in leaves {
+            ret.push(self.load(leaf).await?);
+        }
+        Ok(ret)
+    }
+
+    async fn lookup_primary(&self, key: &PrimaryKey) -> Option<EventLeaf> {
+        let url = self.object_url(&self.index_key(key));
+        let bytes = self.client.get(&url).send().await.ok()?.bytes().await.ok()?;
+        SerializationFormat::Bincode.deserialize(&bytes[..]).ok()
+    }
+
+    async fn lookup_secondary_raw(&self, _collection: &MetaCollection) -> Option<Vec<PrimaryKey>> {
+        // The secondary (collection) index is small enough to be rebuilt from a directory
+        // listing of the bucket prefix rather than a dedicated index object; left for the
+        // next pass since it needs the S3 ListObjectsV2 call rather than a simple GET.
+        None
+    }
+}
+
+impl S3EventStore
+{
+    /// Hands out the next event offset by reading and re-writing a small counter object next
+    /// to the event objects themselves, rather than a `ListObjectsV2` count - a prefix's event
+    /// list only grows, so re-listing it on every append would get slower (and costlier) the
+    /// longer a chain lives. This read-modify-write isn't safe against two writers racing the
+    /// same counter object (a real fix would need a conditional PUT keyed on the object's
+    /// current ETag, which `reqwest` alone doesn't give us); nothing else in this store
+    /// serializes concurrent writers today either, so it doesn't regress an existing guarantee.
+    async fn next_offset(&self) -> Result<u64, StorageError> {
+        let url = self.object_url(&self.counter_key());
+
+        let current: u64 = match self.client.get(&url).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                let bytes = resp.bytes().await
+                    .map_err(|err| StorageError::BackendError(err.to_string()))?;
+                SerializationFormat::Bincode.deserialize(&bytes[..])
+                    .map_err(|err| StorageError::BackendError(err.to_string()))?
+            },
+            _ => 0,
+        };
+
+        let bytes = SerializationFormat::Bincode.serialize(&(current + 1))
+            .map_err(|err| StorageError::BackendError(err.to_string()))?;
+        self.client.put(&url).body(bytes).send().await
+            .map_err(|err| StorageError::BackendError(err.to_string()))?;
+
+        Ok(current)
+    }
+}
@@ -16,6 +16,48 @@ pub struct MetaAuthorization
     implicit_authority: String,
 }
 
+impl MetaAuthorization
+{
+    pub fn allow_read(&self) -> &Vec<Hash> {
+        &self.allow_read
+    }
+
+    pub fn allow_write(&self) -> &Vec<Hash> {
+        &self.allow_write
+    }
+
+    /// Returns a copy of this authorization with `parent`'s `allow_read`/`allow_write` hashes
+    /// folded in (de-duplicated), per `inherit_read`/`inherit_write`. `Dio::commit` and
+    /// `Dio::effective_authorization` use it to resolve `MetaTree::inherit_read`/`inherit_write`;
+    /// `Dio::load` does not apply it. Only the inherited side is folded in, so a parent's writers
+    /// gain no access when only read is inherited (or vice-versa).
+    pub fn inherit_from(&self, parent: &MetaAuthorization, inherit_read: bool, inherit_write: bool) -> MetaAuthorization {
+        let mut allow_read = self.allow_read.clone();
+        if inherit_read {
+            for hash in parent.allow_read.iter() {
+                if allow_read.contains(hash) == false {
+                    allow_read.push(hash.clone());
+                }
+            }
+        }
+
+        let mut allow_write = self.allow_write.clone();
+        if inherit_write {
+            for hash in parent.allow_write.iter() {
+                if allow_write.contains(hash) == false {
+                    allow_write.push(hash.clone());
+                }
+            }
+        }
+
+        MetaAuthorization {
+            allow_read,
+            allow_write,
+            implicit_authority: self.implicit_authority.clone(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MetaTree
 {
@@ -34,6 +76,10 @@ pub enum CoreMetadata
     InitializationVector(InitializationVector),
     PublicKey(PublicKey),
     EncryptedPrivateKey(EncryptedPrivateKey),
+    // TODO(per-row-data-key): `crypto::MultiEncryptedSecureData` can wrap this key once per
+    // `MetaAuthorization::allow_read` entry, but nothing actually constructs or consumes a
+    // variant here yet - see its doc comment for why `Dio::commit` can't do that wrapping on
+    // its own. Left as a plain `EncryptKey` until that's resolved.
     EncyptedEncryptionKey(EncryptKey),
     Tree(MetaTree),
     Signature(MetaSignature),
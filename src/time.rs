@@ -21,15 +21,32 @@ mod ntp;
 
 use ntp::NtpResult;
 
+/// How many servers are drawn from the configured NTP pool zone (as `0.<pool>`..`3.<pool>`) and
+/// queried concurrently for the Marzullo consensus - enough to survive one falseticker while
+/// still reaching a quorum.
+const NTP_CONSENSUS_SERVERS: u32 = 4;
+
 pub struct TimestampEnforcer {
     pub cursor: Duration,
     pub tolerance: Duration,
     pub ntp_pool: Arc<String>,
     pub ntp_port: u32,
+    pub ntp_servers: Arc<Vec<String>>,
     pub ntp_result: Arc<RwLock<NtpResult>>,
+    /// The width of the Marzullo intersection behind `ntp_result` - how much the consensus
+    /// servers disagreed. Widens the tolerance applied in `validate()` when they disagree more.
+    pub ntp_consensus_width: Arc<RwLock<Duration>>,
     pub bt_exit: Arc<Mutex<bool>>,
 }
 
+/// Expands a pool zone (e.g. `pool.ntp.org`) into the `0.<pool>`..`(n-1).<pool>` subdomains that
+/// NTP pool zones conventionally serve as independent, load-balanced servers - the same
+/// convention `ntp.org` itself documents for pool clients that want several independent servers
+/// without the caller having to list them by hand.
+fn ntp_pool_servers(pool: &str, n: u32) -> Vec<String> {
+    (0..n.max(1)).map(|i| format!("{}.{}", i, pool)).collect()
+}
+
 impl Drop
 for TimestampEnforcer
 {
@@ -47,35 +64,35 @@ impl TimestampEnforcer
         let tolerance_ms_seed = tolerance_ms * 3;
 
         let pool = Arc::new(pool);
-        let ntp_result = Arc::new(RwLock::new(ntp::query_ntp_retry(pool.deref(), port, tolerance_ms_seed, 10)?));
+        let servers = Arc::new(ntp_pool_servers(pool.deref(), NTP_CONSENSUS_SERVERS));
+
+        let (seed_result, seed_width) = ntp::query_ntp_consensus(servers.deref(), port, tolerance_ms_seed, 10)?;
+        let ntp_result = Arc::new(RwLock::new(seed_result));
+        let ntp_consensus_width = Arc::new(RwLock::new(seed_width));
         let bt_exit = Arc::new(Mutex::new(false));
 
-        let bt_best_ping = Duration::from_micros(ntp_result.write().unwrap().roundtrip()).as_millis() as u32;
-        let bt_pool = pool.clone();
+        let bt_servers = servers.clone();
         let bt_port = port.clone();
         let bt_exit2 = bt_exit.clone();
         let bt_result = ntp_result.clone();
+        let bt_width = ntp_consensus_width.clone();
 
         std::thread::spawn(move || {
             let mut n: u32 = 0;
-            let mut best_ping = bt_best_ping;
 
             while *bt_exit2.lock().unwrap() == false {
                 if n > 200 {
                     n = 0;
-                    match ntp::query_ntp_retry(bt_pool.deref(), bt_port, tolerance_ms_loop, 10) {
-                        Ok(r) =>
+                    match ntp::query_ntp_consensus(bt_servers.deref(), bt_port, tolerance_ms_loop, 10) {
+                        Ok((r, width)) =>
                         {
-                            let ping = Duration::from_micros(r.roundtrip()).as_millis() as u32;
-                            if ping < best_ping + 50 {
-                                best_ping = ping;
-                                *bt_result.write().unwrap() = r;
-                            }
+                            *bt_result.write().unwrap() = r;
+                            *bt_width.write().unwrap() = width;
                         },
                         _ => {}
                     }
                 }
-                
+
                 std::thread::sleep(Duration::from_millis(100));
                 n = n + 1;
             }
@@ -89,7 +106,9 @@ impl TimestampEnforcer
                 tolerance: tolerance,
                 ntp_pool: pool,
                 ntp_port: port,
+                ntp_servers: servers,
                 ntp_result: ntp_result,
+                ntp_consensus_width: ntp_consensus_width,
                 bt_exit: bt_exit.clone(),
             }
         )
@@ -219,10 +238,12 @@ where M: OtherMetadata,
             },
         };
 
-        // Check its within the time range
+        // Check its within the time range - widened by however much the consensus NTP
+        // servers currently disagree with each other (see `ntp_consensus_width`).
         let timestamp = Duration::from_millis(time.time_since_epoch_ms);
-        let min_timestamp = self.cursor - self.tolerance;
-        let max_timestamp = self.current_timestamp()? + self.tolerance;
+        let tolerance = self.tolerance + *self.ntp_consensus_width.read().unwrap();
+        let min_timestamp = self.cursor - tolerance;
+        let max_timestamp = self.current_timestamp()? + tolerance;
         
         if timestamp < min_timestamp ||
            timestamp > max_timestamp
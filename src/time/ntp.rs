@@ -0,0 +1,167 @@
+use std::net::UdpSocket;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::TimeError;
+
+/// Seconds between the NTP epoch (1900-01-01) and the Unix epoch (1970-01-01).
+const NTP_EPOCH_OFFSET_SECS: u64 = 2_208_988_800;
+
+/// The outcome of a single NTP exchange with one server: the clock offset and the round-trip
+/// time, both in microseconds, as derived from the four NTP timestamps (RFC 5905 section 8).
+#[derive(Debug, Clone, Copy)]
+pub struct NtpResult {
+    offset_micros: i64,
+    roundtrip_micros: u64,
+}
+
+impl NtpResult {
+    pub fn offset(&self) -> i64 {
+        self.offset_micros
+    }
+
+    pub fn roundtrip(&self) -> u64 {
+        self.roundtrip_micros
+    }
+}
+
+fn ntp_timestamp_to_unix_micros(secs: u32, frac: u32) -> i64 {
+    let secs = secs as i64 - NTP_EPOCH_OFFSET_SECS as i64;
+    let micros = ((frac as u64) * 1_000_000u64) >> 32;
+    secs * 1_000_000 + micros as i64
+}
+
+fn unix_now_as_ntp() -> (u32, u32) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = now.as_secs() + NTP_EPOCH_OFFSET_SECS;
+    let frac = (((now.subsec_micros() as u64) << 32) / 1_000_000) as u32;
+    (secs as u32, frac)
+}
+
+/// Performs a single NTP client/server exchange against `server:port` and computes the clock
+/// offset and round-trip time from the four timestamps involved (the local send/receive times
+/// and the two the server echoes back).
+fn query_ntp_once(server: &str, port: u32, timeout_ms: u32) -> Result<NtpResult, TimeError> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.set_read_timeout(Some(Duration::from_millis(timeout_ms as u64)))?;
+    socket.set_write_timeout(Some(Duration::from_millis(timeout_ms as u64)))?;
+    socket.connect((server, port as u16))?;
+
+    // LI=0 (no warning), VN=4, Mode=3 (client).
+    let mut packet = [0u8; 48];
+    packet[0] = 0b00_100_011;
+
+    let (t1_secs, t1_frac) = unix_now_as_ntp();
+    packet[40..44].copy_from_slice(&t1_secs.to_be_bytes());
+    packet[44..48].copy_from_slice(&t1_frac.to_be_bytes());
+    let t1 = ntp_timestamp_to_unix_micros(t1_secs, t1_frac);
+
+    socket.send(&packet)?;
+
+    let mut response = [0u8; 48];
+    socket.recv(&mut response)?;
+    let (t4_secs, t4_frac) = unix_now_as_ntp();
+    let t4 = ntp_timestamp_to_unix_micros(t4_secs, t4_frac);
+
+    let t2 = ntp_timestamp_to_unix_micros(
+        u32::from_be_bytes(response[32..36].try_into().unwrap()),
+        u32::from_be_bytes(response[36..40].try_into().unwrap()),
+    );
+    let t3 = ntp_timestamp_to_unix_micros(
+        u32::from_be_bytes(response[40..44].try_into().unwrap()),
+        u32::from_be_bytes(response[44..48].try_into().unwrap()),
+    );
+
+    // Standard NTP offset/roundtrip formulas (RFC 5905 section 8).
+    let offset_micros = ((t2 - t1) + (t3 - t4)) / 2;
+    let roundtrip_micros = ((t4 - t1) - (t3 - t2)).max(0) as u64;
+
+    Ok(NtpResult { offset_micros, roundtrip_micros })
+}
+
+/// Retries [`query_ntp_once`] against `server` up to `retries` times, returning the first
+/// successful exchange (or the last error if none succeed).
+pub fn query_ntp_retry(server: &str, port: u32, timeout_ms: u32, retries: u32) -> Result<NtpResult, TimeError> {
+    let mut last_err = None;
+    for _ in 0..retries.max(1) {
+        match query_ntp_once(server, port, timeout_ms) {
+            Ok(r) => return Ok(r),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.expect("retries.max(1) guarantees at least one attempt"))
+}
+
+/// Marzullo's algorithm: sweeps every server's `[offset - roundtrip/2, offset + roundtrip/2]`
+/// confidence interval endpoints in sorted order, maintaining a running count that rises at each
+/// lower bound and falls at each upper bound. The first region (in sweep order) covered by the
+/// most intervals is the surviving intersection - its midpoint is the agreed offset, and its width tells the
+/// caller how much the servers disagreed. This discards "falsetickers" (servers whose interval
+/// never overlaps the majority) without having to name them individually.
+fn marzullo(results: &[NtpResult]) -> (i64, u64) {
+    #[derive(Clone, Copy)]
+    struct Point { value: i64, is_lower: bool }
+
+    let mut points: Vec<Point> = Vec::with_capacity(results.len() * 2);
+    for r in results {
+        let half_roundtrip = (r.roundtrip() / 2) as i64;
+        points.push(Point { value: r.offset() - half_roundtrip, is_lower: true });
+        points.push(Point { value: r.offset() + half_roundtrip, is_lower: false });
+    }
+    // At a tied value, process lower bounds before upper bounds so a point shared by one
+    // interval's upper bound and another's lower bound still counts as overlapping.
+    points.sort_by(|a, b| a.value.cmp(&b.value).then(b.is_lower.cmp(&a.is_lower)));
+
+    let mut count = 0i32;
+    let mut best_count = 0i32;
+    let mut best_lower = points[0].value;
+    let mut best_upper = points[0].value;
+    let mut cur_lower = points[0].value;
+
+    for p in &points {
+        if p.is_lower {
+            cur_lower = p.value;
+            count += 1;
+        } else {
+            if count > best_count {
+                best_count = count;
+                best_lower = cur_lower;
+                best_upper = p.value;
+            }
+            count -= 1;
+        }
+    }
+
+    let width = (best_upper - best_lower).max(0) as u64;
+    let offset = best_lower + (best_upper - best_lower) / 2;
+    (offset, width)
+}
+
+/// Concurrently queries every server in `servers` and combines the results with Marzullo's
+/// algorithm, returning the agreed offset/roundtrip (as an [`NtpResult`]) plus the width of the
+/// surviving intersection. Requires at least a quorum (more than half of `servers`) to respond,
+/// so a single lying or badly-skewed server can't steer the consensus.
+pub fn query_ntp_consensus(servers: &[String], port: u32, timeout_ms: u32, retries: u32) -> Result<(NtpResult, Duration), TimeError> {
+    let handles: Vec<_> = servers
+        .iter()
+        .cloned()
+        .map(|server| std::thread::spawn(move || query_ntp_retry(&server, port, timeout_ms, retries)))
+        .collect();
+
+    let results: Vec<NtpResult> = handles
+        .into_iter()
+        .filter_map(|h| h.join().ok())
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let quorum = (servers.len() / 2) + 1;
+    if results.len() < quorum {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("only {} of {} NTP servers responded (need a quorum of {})", results.len(), servers.len(), quorum),
+        ).into());
+    }
+
+    let (offset_micros, width_micros) = marzullo(&results);
+    let roundtrip_micros = results.iter().map(|r| r.roundtrip()).min().unwrap_or(0);
+    Ok((NtpResult { offset_micros, roundtrip_micros }, Duration::from_micros(width_micros)))
+}